@@ -0,0 +1,54 @@
+//! Compares allocation counts and timings for push/pop loops on second::List with and
+//! without a node pool. Run with `cargo bench --bench pool`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use lists::second::List;
+
+/// Counts every allocation made through the global allocator.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ROUNDS: usize = 1_000;
+const BATCH: u64 = 1_000;
+
+/// Pushes and then pops a batch of elements, over and over.
+fn push_pop(name: &str, mut list: List<u64>) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for elem in 0..BATCH {
+            list.push(elem);
+        }
+        while let Some(elem) = list.pop() {
+            black_box(elem);
+        }
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!("{name:<10} {allocations:>10} allocations {elapsed:>12.2?}");
+}
+
+fn main() {
+    println!("{} rounds of pushing then popping {} elements", ROUNDS, BATCH);
+    push_pop("unpooled", List::new());
+    push_pop("pooled", List::with_pool(BATCH as usize));
+}
@@ -0,0 +1,599 @@
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A doubly-linked list whose nodes all live in one Vec, linked by index instead of pointer.
+/// Nodes sit next to each other in memory rather than in separate heap allocations, and
+/// since the Vec owns every node there's no need for Rc or RefCell: peeks hand out plain
+/// references, just like sixth::List. Only IterMut needs unsafe code, to hand out mutable
+/// references to several slots at once.
+/// Removed nodes leave a free slot behind which the next insertion reuses. Each slot counts
+/// how many times it has been freed (its generation), and handles remember the generation
+/// they were created in, so a handle to a removed node can't be mistaken for the node which
+/// took over its slot. Handles also remember which list they came from, so that they can't
+/// be used with another list.
+pub struct List<T> {
+    // Identifies the list to its handles. Every list gets a new one.
+    id: u64,
+    slots: Vec<Slot<T>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    // The first free slot, which links to the rest of the free slots.
+    free: Option<usize>,
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: u64,
+    entry: Entry<T>,
+}
+
+enum Entry<T> {
+    Occupied(Node<T>),
+    Free { next_free: Option<usize> },
+}
+
+struct Node<T> {
+    elem: T,
+    next: Option<usize>,
+    prev: Option<usize>,
+}
+
+/// A handle to a node in a list, used to edit the list around that node in O(1).
+/// Once the node has been popped or removed the handle is stale, and operations using
+/// it fail, as do operations on any list other than the one the handle came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    list: u64,
+    index: usize,
+    generation: u64,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        List { id, slots: Vec::new(), head: None, tail: None, free: None, len: 0 }
+    }
+
+    /// Creates a list with room for `capacity` nodes before its storage has to grow.
+    pub fn with_capacity(capacity: usize) -> Self {
+        List { slots: Vec::with_capacity(capacity), ..List::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        self.push_front_handle(elem);
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.push_back_handle(elem);
+    }
+
+    /// Like push_front, but returns a handle to the new node.
+    pub fn push_front_handle(&mut self, elem: T) -> Handle {
+        let index = self.alloc(elem);
+        self.link(index, None, self.head);
+        self.handle(index)
+    }
+
+    /// Like push_back, but returns a handle to the new node.
+    pub fn push_back_handle(&mut self, elem: T) -> Handle {
+        let index = self.alloc(elem);
+        self.link(index, self.tail, None);
+        self.handle(index)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|index| self.remove_index(index))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|index| self.remove_index(index))
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        self.head.map(|index| &self.node(index).elem)
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|index| &mut self.node_mut(index).elem)
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.tail.map(|index| &self.node(index).elem)
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|index| &mut self.node_mut(index).elem)
+    }
+
+    /// Returns the element the handle refers to, or None if the handle is stale or
+    /// belongs to another list.
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.index(handle).map(|index| &self.node(index).elem)
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.index(handle).map(|index| &mut self.node_mut(index).elem)
+    }
+
+    /// Unlinks the handle's node from the list and returns its element.
+    /// Returns None if the handle is stale or belongs to another list.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        self.index(handle).map(|index| self.remove_index(index))
+    }
+
+    /// Inserts an element directly before the handle's node and returns a handle to it.
+    /// Gives the element back if the handle is stale or belongs to another list.
+    pub fn insert_before(&mut self, handle: Handle, elem: T) -> Result<Handle, T> {
+        let next = match self.index(handle) {
+            Some(index) => index,
+            None => return Err(elem),
+        };
+        let prev = self.node(next).prev;
+        let index = self.alloc(elem);
+        self.link(index, prev, Some(next));
+        Ok(self.handle(index))
+    }
+
+    /// Inserts an element directly after the handle's node and returns a handle to it.
+    /// Gives the element back if the handle is stale or belongs to another list.
+    pub fn insert_after(&mut self, handle: Handle, elem: T) -> Result<Handle, T> {
+        let prev = match self.index(handle) {
+            Some(index) => index,
+            None => return Err(elem),
+        };
+        let next = self.node(prev).next;
+        let index = self.alloc(elem);
+        self.link(index, Some(prev), next);
+        Ok(self.handle(index))
+    }
+
+    /// Moves the handle's node to the front of the list, keeping the handle valid.
+    /// Returns false if the handle is stale or belongs to another list.
+    pub fn move_to_front(&mut self, handle: Handle) -> bool {
+        match self.index(handle) {
+            Some(index) => {
+                self.unlink(index);
+                self.link(index, None, self.head);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { list: self, front: self.head, back: self.tail, len: self.len }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.as_mut_ptr(),
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    fn handle(&self, index: usize) -> Handle {
+        Handle { list: self.id, index, generation: self.slots[index].generation }
+    }
+
+    /// Returns the index of the handle's node, if it's still in the list.
+    fn index(&self, handle: Handle) -> Option<usize> {
+        if handle.list != self.id {
+            return None;
+        }
+        match self.slots.get(handle.index) {
+            Some(Slot { generation, entry: Entry::Occupied(_) }) if *generation == handle.generation => {
+                Some(handle.index)
+            }
+            _ => None,
+        }
+    }
+
+    fn node(&self, index: usize) -> &Node<T> {
+        match &self.slots[index].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        match &mut self.slots[index].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+
+    /// Stores an unlinked node in a free slot, or a new one if there are none, and returns its index.
+    fn alloc(&mut self, elem: T) -> usize {
+        let node = Entry::Occupied(Node { elem, next: None, prev: None });
+        self.len += 1;
+        match self.free {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                if let Entry::Free { next_free } = slot.entry {
+                    self.free = next_free;
+                }
+                slot.entry = node;
+                index
+            }
+            None => {
+                self.slots.push(Slot { generation: 0, entry: node });
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Unlinks a node, frees its slot and returns its element.
+    fn remove_index(&mut self, index: usize) -> T {
+        self.unlink(index);
+        self.len -= 1;
+        let slot = &mut self.slots[index];
+        // Any handles to the node go stale
+        slot.generation += 1;
+        let entry = std::mem::replace(&mut slot.entry, Entry::Free { next_free: self.free });
+        self.free = Some(index);
+        match entry {
+            Entry::Occupied(node) => node.elem,
+            Entry::Free { .. } => unreachable!("removed a free slot"),
+        }
+    }
+
+    /// Joins a node's neighbours to each other, leaving the node unlinked.
+    fn unlink(&mut self, index: usize) {
+        let Node { prev, next, .. } = *self.node(index);
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    /// Links an unlinked node in between prev and next, which must be adjacent
+    /// (or the ends of the list when None).
+    fn link(&mut self, index: usize, prev: Option<usize>, next: Option<usize>) {
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(index),
+            None => self.head = Some(index),
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+        let node = self.node_mut(index);
+        node.prev = prev;
+        node.next = next;
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> crate::Stack<T> for List<T> {
+    fn push(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+impl<T> crate::Queue<T> for List<T> {
+    fn enqueue(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+impl<T> crate::Deque<T> for List<T> {
+    fn push_front(&mut self, elem: T) {
+        List::push_front(self, elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        List::push_back(self, elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        List::pop_front(self)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        List::pop_back(self)
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Iterates over the list from either end, counting down the elements left so that
+/// the ends stop when they meet.
+pub struct Iter<'a, T> {
+    list: &'a List<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|index| {
+            self.len -= 1;
+            let node = self.list.node(index);
+            self.front = node.next;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|index| {
+            self.len -= 1;
+            let node = self.list.node(index);
+            self.back = node.prev;
+            &node.elem
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Like Iter, but hands out mutable references. Safe code can't borrow several slots of a
+/// Vec mutably at once, so this keeps a pointer to the slots instead of a reference to the list.
+pub struct IterMut<'a, T> {
+    slots: *mut Slot<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+    _list: PhantomData<&'a mut List<T>>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    /// Returns the node at the given index for the lifetime of the iterator's borrow.
+    ///
+    /// # Safety
+    /// The index must be a linked node which hasn't been returned before.
+    unsafe fn node(&mut self, index: usize) -> &'a mut Node<T> {
+        // SAFETY: the list is mutably borrowed for 'a so its slots can't move or change, and
+        // the caller guarantees this is the only reference to the node.
+        match unsafe { &mut (*self.slots.add(index)).entry } {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|index| {
+            self.len -= 1;
+            // SAFETY: counting down the length stops either end from reaching a node twice.
+            let node = unsafe { self.node(index) };
+            self.front = node.next;
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|index| {
+            self.len -= 1;
+            // SAFETY: as for next.
+            let node = unsafe { self.node(index) };
+            self.back = node.prev;
+            &mut node.elem
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    fn drain(list: List<i32>) -> Vec<i32> {
+        list.into_iter().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        list.push_back(4);
+        list.push_back(5);
+        assert_eq!(list.pop_front(), Some(4));
+        assert_eq!(list.pop_front(), Some(5));
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek_front(), None);
+        assert_eq!(list.peek_back_mut(), None);
+        list.push_back(1);
+        list.push_back(2);
+        assert_eq!(list.peek_front(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&2));
+        *list.peek_front_mut().unwrap() = 10;
+        *list.peek_back_mut().unwrap() = 20;
+        assert_eq!(drain(list), vec![10, 20]);
+    }
+
+    #[test]
+    fn slots_are_reused() {
+        let mut list = List::with_capacity(2);
+        list.push_back(1);
+        list.push_back(2);
+        list.pop_front();
+        list.pop_front();
+        list.push_back(3);
+        list.push_back(4);
+        list.push_back(5);
+        assert_eq!(list.slots.len(), 3);
+        assert_eq!(drain(list), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn handles() {
+        let mut list = List::new();
+        let one = list.push_back_handle(1);
+        let three = list.push_back_handle(3);
+        let two = list.insert_before(three, 2).unwrap();
+        list.insert_after(three, 4).unwrap();
+        assert_eq!(list.get(two), Some(&2));
+        *list.get_mut(two).unwrap() = 20;
+
+        assert!(list.move_to_front(three));
+        assert_eq!(list.remove(one), Some(1));
+        assert_eq!(list.remove(one), None);
+        assert_eq!(list.get(one), None);
+        assert_eq!(list.insert_after(one, 5), Err(5));
+        assert!(!list.move_to_front(one));
+
+        // The removed node's slot is reused, but the old handle still doesn't match it
+        let six = list.push_front_handle(6);
+        assert_eq!(six.index, one.index);
+        assert_eq!(list.get(one), None);
+        assert_eq!(list.get(six), Some(&6));
+        assert_eq!(drain(list), vec![6, 3, 20, 4]);
+    }
+
+    #[test]
+    fn handles_from_another_list() {
+        let mut a = List::new();
+        let mut b = List::new();
+        let from_a = a.push_back_handle(1);
+        // b's node is in the same slot with the same generation, but isn't the handle's node
+        let from_b = b.push_back_handle(99);
+        assert_eq!(from_a.index, from_b.index);
+
+        assert_eq!(b.get(from_a), None);
+        assert_eq!(b.get_mut(from_a), None);
+        assert_eq!(b.remove(from_a), None);
+        assert_eq!(b.insert_before(from_a, 2), Err(2));
+        assert_eq!(b.insert_after(from_a, 3), Err(3));
+        assert!(!b.move_to_front(from_a));
+        assert_eq!(drain(b), vec![99]);
+        assert_eq!(a.remove(from_a), Some(1));
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list = List::new();
+        for elem in 1..=5 {
+            list.push_back(elem);
+        }
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.rev().copied().collect::<Vec<_>>(), vec![4, 3, 2]);
+
+        let mut iter = list.iter_mut();
+        *iter.next_back().unwrap() *= 10;
+        for elem in iter {
+            *elem += 1;
+        }
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next_back(), Some(50));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+    }
+}
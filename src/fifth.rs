@@ -0,0 +1,349 @@
+use std::iter::FusedIterator;
+use std::ptr;
+
+/// A singly-linked queue which keeps a raw pointer to its last node so that
+/// elements can be pushed onto the back in O(1).
+/// Safe Rust can't express a node which is owned by the node before it and also pointed
+/// to by the list, so every link is a raw pointer and the list manages the memory itself.
+/// Nodes are only ever turned back into Boxes when they are popped, and references are only
+/// created for as long as the list is borrowed, which keeps Miri happy.
+pub struct List<T> {
+    head: Link<T>,
+    tail: *mut Node<T>,
+    len: usize,
+}
+
+type Link<T> = *mut Node<T>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: ptr::null_mut(), tail: ptr::null_mut(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds an element to the back of the queue.
+    pub fn push(&mut self, elem: T) {
+        let new_tail = Box::into_raw(Box::new(Node {
+            elem,
+            next: ptr::null_mut(),
+        }));
+        if self.tail.is_null() {
+            // Empty list, the new node is the head as well
+            self.head = new_tail;
+        } else {
+            // SAFETY: a non-null tail always points to a live node owned by this list.
+            unsafe {
+                (*self.tail).next = new_tail;
+            }
+        }
+        self.tail = new_tail;
+        self.len += 1;
+    }
+
+    /// Removes the element at the front of the queue.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.head.is_null() {
+            return None;
+        }
+        // SAFETY: a non-null head was created by Box::into_raw in push and hasn't been freed.
+        let head = unsafe { Box::from_raw(self.head) };
+        self.head = head.next;
+        self.len -= 1;
+        if self.head.is_null() {
+            // Emptied the list, so the tail pointer is dangling
+            self.tail = ptr::null_mut();
+        }
+        Some(head.elem)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        // SAFETY: the head is null or a live node, and the borrow of self keeps it alive.
+        unsafe { self.head.as_ref().map(|node| &node.elem) }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as for peek, and the mutable borrow of self makes the reference unique.
+        unsafe { self.head.as_mut().map(|node| &mut node.elem) }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        // SAFETY: as for peek.
+        unsafe { Iter { next: self.head.as_ref(), len: self.len } }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        // SAFETY: as for peek_mut.
+        unsafe { IterMut { next: self.head.as_mut(), len: self.len } }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> crate::Queue<T> for List<T> {
+    fn enqueue(&mut self, elem: T) {
+        self.push(elem)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            // SAFETY: the next node is null or live, and stays alive while the list is borrowed.
+            self.next = unsafe { node.next.as_ref() };
+            self.len -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            // SAFETY: as for Iter, and each node is only handed out once.
+            self.next = unsafe { node.next.as_mut() };
+            self.len -= 1;
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+        assert_eq!(list.pop(), None);
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+
+        list.push(4);
+        list.push(5);
+
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), None);
+
+        // Check the tail is reset when the list is emptied
+        list.push(6);
+        list.push(7);
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), Some(7));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        assert_eq!(list.peek(), Some(&1));
+        assert_eq!(list.peek_mut(), Some(&mut 1));
+        if let Some(value) = list.peek_mut() {
+            *value = 42;
+        }
+        assert_eq!(list.peek(), Some(&42));
+        assert_eq!(list.pop(), Some(42));
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next(), Some(&mut 1));
+        assert_eq!(iter.next(), Some(&mut 2));
+        assert_eq!(iter.next(), Some(&mut 3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.len(), 2);
+
+        let mut iter = list.iter();
+        iter.next();
+        assert_eq!(iter.len(), 1);
+        assert_eq!(list.iter_mut().len(), 2);
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 2);
+        iter.next();
+        iter.next();
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+    }
+
+    /// Interleaves every kind of access, so that running the tests under Miri
+    /// checks the raw pointers against each other.
+    #[test]
+    fn miri_food() {
+        let mut list = List::new();
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert_eq!(list.pop(), Some(1));
+        list.push(4);
+        assert_eq!(list.pop(), Some(2));
+        list.push(5);
+
+        assert_eq!(list.peek(), Some(&3));
+        list.push(6);
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert_eq!(list.peek(), Some(&30));
+        assert_eq!(list.pop(), Some(30));
+
+        for elem in list.iter_mut() {
+            *elem *= 100;
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&400));
+        assert_eq!(iter.next(), Some(&500));
+        assert_eq!(iter.next(), Some(&600));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(list.pop(), Some(400));
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert_eq!(list.peek(), Some(&5000));
+        list.push(7);
+
+        // Drop it on the ground and let the dtor exercise itself
+    }
+}
@@ -1,25 +1,34 @@
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::iter::FusedIterator;
 
-/// A doubly-linked list that creates heap-allocated reference cycles.
+/// A doubly-linked list built from reference-counted nodes.
 /// Not a good idea, just for demonstration purposes.
-/// Has no .iter() or .iter_mut() implementations because it is too difficult
-/// to give out references to items in the list and ensure those references stay valid.
-/// There will always be the possibility that the user will pop() a node that they are holding a
-/// reference to, which will cause a panic. This is why interior mutability is better for
+/// Each node owns the next node, but only holds a weak pointer back to the previous one, so the
+/// nodes never form reference cycles. If dropping the list is interrupted (e.g. by a panicking
+/// element destructor) the nodes which are left are still freed as their owners go away.
+/// Handing out references to items in the list is difficult: .iter() and .iter_mut() can only
+/// give out Ref and RefMut guards, and need a little unsafe code to walk the links while the
+/// list is borrowed. There will always be the possibility that the user will pop() a node that
+/// they are holding a reference to, which will cause a panic. This is why interior mutability is better for
 /// writing safe applications (where the use can be strictly controlled to prevent panics) than
 /// for writing safe libraries (where the user can do whatever they want).
 pub struct List<T> {
     head: Link<T>,
     tail: Link<T>,
+    len: usize,
+    // Identifies this list so that handles to another list's nodes can be rejected.
+    id: Rc<()>,
 }
 
 type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+type WeakLink<T> = Option<Weak<RefCell<Node<T>>>>;
 
 struct Node<T> {
     elem: T,
     next: Link<T>,
-    prev: Link<T>,
+    prev: WeakLink<T>,
 }
 
 impl<T> Node<T> {
@@ -34,9 +43,57 @@ impl<T> Node<T> {
 
 pub struct IntoIter<T>(List<T>);
 
+/// A handle to a node in a list, used to edit the list around that node in O(1).
+/// The handle doesn't keep its node alive: once the node has been popped or removed,
+/// or the list has been dropped, the handle is stale and operations using it fail.
+pub struct NodeHandle<T> {
+    node: Weak<RefCell<Node<T>>>,
+    list: Weak<()>,
+}
+
+impl<T> NodeHandle<T> {
+    /// Returns true if the node this handle refers to is no longer in its list.
+    pub fn is_stale(&self) -> bool {
+        self.node.strong_count() == 0
+    }
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        NodeHandle { node: self.node.clone(), list: self.list.clone() }
+    }
+}
+
+/// Why a fallible operation on the list couldn't go ahead.
+/// The list is left unchanged whenever one of these is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListError {
+    /// A node is still borrowed through a Ref or RefMut which hasn't been dropped
+    /// (e.g. a guard which was leaked with mem::forget).
+    Borrowed,
+}
+
+impl fmt::Display for ListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListError::Borrowed => write!(f, "list node is still borrowed"),
+        }
+    }
+}
+
+impl std::error::Error for ListError {}
+
 impl<T> List<T> {
     pub fn new() -> Self {
-        List { head: None, tail: None }
+        List { head: None, tail: None, len: 0, id: Rc::new(()) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn push_front(&mut self, elem: T) {
@@ -45,7 +102,7 @@ impl<T> List<T> {
         match self.head.take() {
             Some(old_head) => {
                 // Non-empty list, need to connect the old_head
-                old_head.borrow_mut().prev = Some(new_head.clone());  // +1 link to new_head
+                old_head.borrow_mut().prev = Some(Rc::downgrade(&new_head));  // +1 weak link to new_head
                 new_head.borrow_mut().next = Some(old_head);  // +1 link to old_head
                 self.head = Some(new_head);  // +1 link to new_head, -1 link to old_head
             }
@@ -55,6 +112,7 @@ impl<T> List<T> {
                 self.head = Some(new_head);  // +1 link to new_head
             }
         }
+        self.len += 1;
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
@@ -63,7 +121,7 @@ impl<T> List<T> {
             match old_head.borrow_mut().next.take() {
                 Some(new_head) => {  // -1 reference to new head
                     // Not emptying list
-                    new_head.borrow_mut().prev.take();  // -1 reference to old head
+                    new_head.borrow_mut().prev.take();  // -1 weak reference to old head
                     self.head = Some(new_head);  // +1 reference to new head
                 }
                 None => {
@@ -71,6 +129,7 @@ impl<T> List<T> {
                     self.tail.take();  // -1 reference to old head
                 }
             }
+            self.len -= 1;
             // Here we unwrap and return the actual value since
             // there are no more references to it in the list
             Rc::try_unwrap(old_head).ok().unwrap().into_inner().elem
@@ -80,7 +139,7 @@ impl<T> List<T> {
     /// Returns a Ref because the RefCell type only allows borrowing via a
     /// Ref in order to maintain reference count. Lifetime of Ref = lifetime of value
     /// This is not a good solution, it's just what's required for this example to work.
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         self.head.as_ref().map(|node| {
             Ref::map(node.borrow(), |node| &node.elem)
         })
@@ -91,7 +150,7 @@ impl<T> List<T> {
         match self.tail.take() {  // -1 old tail
             Some(old_tail) => {
                 old_tail.borrow_mut().next = Some(new_tail.clone());  // +1 new tail
-                new_tail.borrow_mut().prev = Some(old_tail);  // +1 old tail
+                new_tail.borrow_mut().prev = Some(Rc::downgrade(&old_tail));  // +1 weak old tail
                 self.tail = Some(new_tail); // +1 new tail
             }
             None => {
@@ -100,11 +159,13 @@ impl<T> List<T> {
                 self.tail = Some(new_tail);
             }
         }
+        self.len += 1;
     }
 
     pub fn pop_back(&mut self) -> Option<T> {
         self.tail.take().map(|old_tail| { // -1 old tail
-            match old_tail.borrow_mut().prev.take() { // -1 new tail
+            // -1 weak new tail. It's owned by the node before it (or the head), so it's still alive
+            match old_tail.borrow_mut().prev.take().and_then(|prev| prev.upgrade()) {
                 Some(new_tail) => {
                     new_tail.borrow_mut().next.take();  // -1 old tail
                     self.tail = Some(new_tail); // +1 new tail
@@ -114,26 +175,318 @@ impl<T> List<T> {
                     self.head.take();
                 }
             }
+            self.len -= 1;
             Rc::try_unwrap(old_tail).ok().unwrap().into_inner().elem
         })
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.tail.as_ref().map(|node| Ref::map(node.borrow(), |node| &node.elem))
     }
 
-    pub fn peek_back_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.tail.as_ref().map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    pub fn peek_front_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.head.as_ref().map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+    /// Like pop_front, but returns an error instead of panicking if the node can't be unlinked.
+    pub fn try_pop_front(&mut self) -> Result<Option<T>, ListError> {
+        match &self.head {
+            Some(head) => {
+                Self::check_poppable(head, |node| node.next.clone())?;
+                Ok(self.pop_front())
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Like pop_back, but returns an error instead of panicking if the node can't be unlinked.
+    pub fn try_pop_back(&mut self) -> Result<Option<T>, ListError> {
+        match &self.tail {
+            Some(tail) => {
+                Self::check_poppable(tail, |node| node.prev.as_ref().and_then(Weak::upgrade))?;
+                Ok(self.pop_back())
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn try_peek_front(&self) -> Result<Option<Ref<'_, T>>, ListError> {
+        Self::try_borrow(&self.head)
+    }
+
+    pub fn try_peek_front_mut(&mut self) -> Result<Option<RefMut<'_, T>>, ListError> {
+        Self::try_borrow_mut(&self.head)
+    }
+
+    pub fn try_peek_back(&self) -> Result<Option<Ref<'_, T>>, ListError> {
+        Self::try_borrow(&self.tail)
+    }
+
+    pub fn try_peek_back_mut(&mut self) -> Result<Option<RefMut<'_, T>>, ListError> {
+        Self::try_borrow_mut(&self.tail)
+    }
+
+    fn try_borrow(link: &Link<T>) -> Result<Option<Ref<'_, T>>, ListError> {
+        link.as_ref()
+            .map(|node| node.try_borrow().map(|node| Ref::map(node, |node| &node.elem)))
+            .transpose()
+            .map_err(|_| ListError::Borrowed)
+    }
+
+    fn try_borrow_mut(link: &Link<T>) -> Result<Option<RefMut<'_, T>>, ListError> {
+        link.as_ref()
+            .map(|node| node.try_borrow_mut().map(|node| RefMut::map(node, |node| &mut node.elem)))
+            .transpose()
+            .map_err(|_| ListError::Borrowed)
+    }
+
+    /// Checks that the node at one end of the list can be popped without panicking:
+    /// it and its neighbour (picked out by `neighbour`) must not be borrowed.
+    /// Only the list holds strong links to its nodes, so once unlinked a node can always be
+    /// moved out.
+    fn check_poppable(
+        end: &Rc<RefCell<Node<T>>>,
+        neighbour: impl FnOnce(&Node<T>) -> Link<T>,
+    ) -> Result<(), ListError> {
+        let node = end.try_borrow_mut().map_err(|_| ListError::Borrowed)?;
+        if let Some(neighbour) = neighbour(&node) {
+            neighbour.try_borrow_mut().map_err(|_| ListError::Borrowed)?;
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { front: self.head.as_deref(), back: self.tail.as_deref(), len: self.len }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { front: self.head.as_deref(), back: self.tail.as_deref(), len: self.len }
+    }
+
+    /// Like push_front, but returns a handle to the new node.
+    pub fn push_front_handle(&mut self, elem: T) -> NodeHandle<T> {
+        self.push_front(elem);
+        self.handle(self.head.as_ref().unwrap())
+    }
+
+    /// Like push_back, but returns a handle to the new node.
+    pub fn push_back_handle(&mut self, elem: T) -> NodeHandle<T> {
+        self.push_back(elem);
+        self.handle(self.tail.as_ref().unwrap())
+    }
+
+    /// Unlinks the handle's node from the list and returns its element.
+    /// Returns None if the handle is stale or belongs to another list.
+    pub fn remove(&mut self, handle: &NodeHandle<T>) -> Option<T> {
+        let node = self.node(handle)?;
+        self.unlink(&node);
+        self.len -= 1;
+        // The handle's reference is weak, so ours is the last one left
+        Some(Rc::try_unwrap(node).ok().unwrap().into_inner().elem)
+    }
+
+    /// Inserts an element directly before the handle's node and returns a handle to it.
+    /// Gives the element back if the handle is stale or belongs to another list.
+    pub fn insert_before(&mut self, handle: &NodeHandle<T>, elem: T) -> Result<NodeHandle<T>, T> {
+        let next = match self.node(handle) {
+            Some(node) => node,
+            None => return Err(elem),
+        };
+        let prev = next.borrow().prev.as_ref().and_then(Weak::upgrade);
+        let new_node = Node::new(elem);
+        self.link(&new_node, prev, Some(next));
+        self.len += 1;
+        Ok(self.handle(&new_node))
+    }
+
+    /// Inserts an element directly after the handle's node and returns a handle to it.
+    /// Gives the element back if the handle is stale or belongs to another list.
+    pub fn insert_after(&mut self, handle: &NodeHandle<T>, elem: T) -> Result<NodeHandle<T>, T> {
+        let prev = match self.node(handle) {
+            Some(node) => node,
+            None => return Err(elem),
+        };
+        let next = prev.borrow().next.clone();
+        let new_node = Node::new(elem);
+        self.link(&new_node, Some(prev), next);
+        self.len += 1;
+        Ok(self.handle(&new_node))
+    }
+
+    /// Moves the handle's node to the front of the list, keeping the handle valid.
+    /// Returns false if the handle is stale or belongs to another list.
+    pub fn move_to_front(&mut self, handle: &NodeHandle<T>) -> bool {
+        match self.node(handle) {
+            Some(node) => {
+                self.unlink(&node);
+                let next = self.head.clone();
+                self.link(&node, None, next);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Like remove, but returns an error instead of panicking if the node or one of its
+    /// neighbours is borrowed.
+    pub fn try_remove(&mut self, handle: &NodeHandle<T>) -> Result<Option<T>, ListError> {
+        if let Some(node) = self.node(handle) {
+            Self::check_relinkable(&node)?;
+        }
+        // The node has to be let go of first, or remove couldn't move its element out
+        Ok(self.remove(handle))
+    }
+
+    /// Like insert_before, but returns an error instead of panicking if the node or one of
+    /// its neighbours is borrowed. The element is given back along with the error.
+    pub fn try_insert_before(
+        &mut self,
+        handle: &NodeHandle<T>,
+        elem: T,
+    ) -> Result<Result<NodeHandle<T>, T>, (ListError, T)> {
+        if let Some(node) = self.node(handle) {
+            if let Err(err) = Self::check_relinkable(&node) {
+                return Err((err, elem));
+            }
+        }
+        Ok(self.insert_before(handle, elem))
+    }
+
+    /// Like insert_after, but returns an error instead of panicking if the node or one of
+    /// its neighbours is borrowed. The element is given back along with the error.
+    pub fn try_insert_after(
+        &mut self,
+        handle: &NodeHandle<T>,
+        elem: T,
+    ) -> Result<Result<NodeHandle<T>, T>, (ListError, T)> {
+        if let Some(node) = self.node(handle) {
+            if let Err(err) = Self::check_relinkable(&node) {
+                return Err((err, elem));
+            }
+        }
+        Ok(self.insert_after(handle, elem))
+    }
+
+    /// Like move_to_front, but returns an error instead of panicking if the node, one of its
+    /// neighbours or the head of the list is borrowed.
+    pub fn try_move_to_front(&mut self, handle: &NodeHandle<T>) -> Result<bool, ListError> {
+        if let Some(node) = self.node(handle) {
+            Self::check_relinkable(&node)?;
+            if let Some(head) = &self.head {
+                head.try_borrow_mut().map_err(|_| ListError::Borrowed)?;
+            }
+        }
+        Ok(self.move_to_front(handle))
+    }
+
+    /// Checks that a node can be unlinked, or have a node linked in next to it, without
+    /// panicking: neither it nor its neighbours may be borrowed.
+    fn check_relinkable(node: &Rc<RefCell<Node<T>>>) -> Result<(), ListError> {
+        let node = node.try_borrow_mut().map_err(|_| ListError::Borrowed)?;
+        let prev = node.prev.as_ref().and_then(Weak::upgrade);
+        for neighbour in prev.iter().chain(&node.next) {
+            neighbour.try_borrow_mut().map_err(|_| ListError::Borrowed)?;
+        }
+        Ok(())
+    }
+
+    fn handle(&self, node: &Rc<RefCell<Node<T>>>) -> NodeHandle<T> {
+        NodeHandle { node: Rc::downgrade(node), list: Rc::downgrade(&self.id) }
     }
 
+    fn node(&self, handle: &NodeHandle<T>) -> Option<Rc<RefCell<Node<T>>>> {
+        if handle.list.as_ptr() != Rc::as_ptr(&self.id) {
+            return None;
+        }
+        handle.node.upgrade()
+    }
+
+    /// Removes all links to and from the node, joining its neighbours to each other.
+    fn unlink(&mut self, node: &Rc<RefCell<Node<T>>>) {
+        let prev = node.borrow_mut().prev.take().and_then(|prev| prev.upgrade());
+        let next = node.borrow_mut().next.take();
+        match &prev {
+            Some(prev) => prev.borrow_mut().next = next.clone(),
+            None => self.head = next.clone(),
+        }
+        match next {
+            Some(next) => next.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev,
+        }
+    }
+
+    /// Links an unlinked node in between prev and next, which must be adjacent
+    /// (or the ends of the list when None).
+    fn link(&mut self, node: &Rc<RefCell<Node<T>>>, prev: Link<T>, next: Link<T>) {
+        match &prev {
+            Some(prev) => prev.borrow_mut().next = Some(node.clone()),
+            None => self.head = Some(node.clone()),
+        }
+        match &next {
+            Some(next) => next.borrow_mut().prev = Some(Rc::downgrade(node)),
+            None => self.tail = Some(node.clone()),
+        }
+        let mut new_node = node.borrow_mut();
+        new_node.prev = prev.as_ref().map(Rc::downgrade);
+        new_node.next = next;
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> crate::Stack<T> for List<T> {
+    fn push(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+impl<T> crate::Queue<T> for List<T> {
+    fn enqueue(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+impl<T> crate::Deque<T> for List<T> {
+    fn push_front(&mut self, elem: T) {
+        List::push_front(self, elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        List::push_back(self, elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        List::pop_front(self)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        List::pop_back(self)
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
 }
 
 impl<T> Iterator for IntoIter<T> {
@@ -142,6 +495,10 @@ impl<T> Iterator for IntoIter<T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop_front()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
 }
 
 impl<T> DoubleEndedIterator for IntoIter<T> {
@@ -150,8 +507,123 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Follows the link picked out of a node by `link`, returning the node at the other end.
+/// Links are only changed through a mutable borrow of the list, so as long as the list is
+/// borrowed every node reachable from it stays alive.
+fn follow<T>(
+    node: &RefCell<Node<T>>,
+    link: impl FnOnce(&Node<T>) -> Option<*const RefCell<Node<T>>>,
+) -> Option<&RefCell<Node<T>>> {
+    let node = node.borrow();
+    // SAFETY: `node` comes from a list which is borrowed for at least as long as the
+    // returned reference, and that borrow keeps the linked node alive.
+    link(&node).map(|next| unsafe { &*next })
+}
+
+/// Iterates over the list from either end, yielding a Ref guard for each element.
+pub struct Iter<'a, T> {
+    front: Option<&'a RefCell<Node<T>>>,
+    back: Option<&'a RefCell<Node<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.map(|node| {
+            self.len -= 1;
+            if self.back.is_some_and(|back| std::ptr::eq(node, back)) {
+                // The ends have met, this is the last element
+                self.front = None;
+                self.back = None;
+            } else {
+                self.front = follow(node, |node| node.next.as_ref().map(Rc::as_ptr));
+            }
+            Ref::map(node.borrow(), |node| &node.elem)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.map(|node| {
+            self.len -= 1;
+            if self.front.is_some_and(|front| std::ptr::eq(node, front)) {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.back = follow(node, |node| node.prev.as_ref().map(Weak::as_ptr));
+            }
+            Ref::map(node.borrow(), |node| &node.elem)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Iterates over the list from either end, yielding a RefMut guard for each element.
+/// The next link is always read before a node's guard is handed out, so the iterator never
+/// needs to borrow a node which the user may still be holding.
+pub struct IterMut<'a, T> {
+    front: Option<&'a RefCell<Node<T>>>,
+    back: Option<&'a RefCell<Node<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = RefMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.map(|node| {
+            self.len -= 1;
+            if self.back.is_some_and(|back| std::ptr::eq(node, back)) {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.front = follow(node, |node| node.next.as_ref().map(Rc::as_ptr));
+            }
+            RefMut::map(node.borrow_mut(), |node| &mut node.elem)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.map(|node| {
+            self.len -= 1;
+            if self.front.is_some_and(|front| std::ptr::eq(node, front)) {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.back = follow(node, |node| node.prev.as_ref().map(Weak::as_ptr));
+            }
+            RefMut::map(node.borrow_mut(), |node| &mut node.elem)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
 /// Drops list by popping each element from the front of the queue,
 /// which removes all references to the element, until the queue is empty.
+/// Popping one node at a time avoids recursing down the whole chain of next links.
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         while self.pop_front().is_some() {}
@@ -160,7 +632,38 @@ impl<T> Drop for List<T> {
 
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{List, ListError};
+    use std::cell::Cell;
+    use std::mem;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    /// Counts how many times it has been dropped, and optionally panics when dropped.
+    struct Counted {
+        drops: Rc<Cell<usize>>,
+        panic_on_drop: bool,
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panic_on_drop {
+                panic!("element dropped");
+            }
+        }
+    }
+
+    fn counted_list(len: usize, drops: &Rc<Cell<usize>>) -> List<Counted> {
+        let mut list = List::new();
+        for _ in 0..len {
+            list.push_back(Counted { drops: drops.clone(), panic_on_drop: false });
+        }
+        list
+    }
+
+    fn drain(list: List<i32>) -> Vec<i32> {
+        list.into_iter().collect()
+    }
 
     #[test]
     fn basics() {
@@ -233,4 +736,297 @@ mod test {
         assert_eq!(iter.next(), None);
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn handle_remove() {
+        let mut list = List::new();
+        let one = list.push_back_handle(1);
+        let two = list.push_back_handle(2);
+        let three = list.push_back_handle(3);
+
+        assert_eq!(list.remove(&two), Some(2));
+        assert!(two.is_stale());
+        assert_eq!(list.remove(&two), None);
+        assert_eq!(list.remove(&three), Some(3));
+        assert_eq!(&*list.peek_back().unwrap(), &1);
+        assert_eq!(list.remove(&one), Some(1));
+        assert!(list.peek_front().is_none());
+        assert!(list.peek_back().is_none());
+
+        list.push_front(4);
+        let five = list.push_front_handle(5);
+        assert_eq!(list.remove(&five), Some(5));
+        assert_eq!(drain(list), vec![4]);
+    }
+
+    #[test]
+    fn handle_stale() {
+        let mut list = List::new();
+        let front = list.push_front_handle(1);
+        let back = list.push_back_handle(2);
+        assert!(!front.is_stale());
+        assert_eq!(list.pop_front(), Some(1));
+        assert!(front.is_stale());
+        assert!(!back.is_stale());
+        assert_eq!(list.insert_after(&front, 3).err(), Some(3));
+        assert!(!list.move_to_front(&front));
+
+        let mut other = List::new();
+        other.push_back(4);
+        assert_eq!(other.remove(&back), None);
+        assert_eq!(other.insert_before(&back, 5).err(), Some(5));
+        assert!(!other.move_to_front(&back));
+
+        drop(list);
+        assert!(back.is_stale());
+        assert_eq!(drain(other), vec![4]);
+    }
+
+    #[test]
+    fn handle_insert() {
+        let mut list = List::new();
+        let two = list.push_back_handle(2);
+        let one = list.insert_before(&two, 1).ok().unwrap();
+        let four = list.insert_after(&two, 4).ok().unwrap();
+        list.insert_before(&four, 3).ok().unwrap();
+        list.insert_before(&one, 0).ok().unwrap();
+        list.insert_after(&four, 5).ok().unwrap();
+        assert_eq!(&*list.peek_front().unwrap(), &0);
+        assert_eq!(&*list.peek_back().unwrap(), &5);
+        assert_eq!(drain(list), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn handle_move_to_front() {
+        let mut list = List::new();
+        let one = list.push_back_handle(1);
+        list.push_back(2);
+        let three = list.push_back_handle(3);
+
+        assert!(list.move_to_front(&three));
+        assert!(list.move_to_front(&three));
+        assert_eq!(&*list.peek_back().unwrap(), &2);
+        assert!(list.move_to_front(&one));
+        assert!(!three.is_stale());
+        assert_eq!(list.remove(&three), Some(3));
+        assert_eq!(drain(list), vec![1, 2]);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        assert!(list.iter().next().is_none());
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        let mut iter = list.iter();
+        assert_eq!(*iter.next().unwrap(), 1);
+        assert_eq!(*iter.next_back().unwrap(), 3);
+        let two = iter.next().unwrap();
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+        assert_eq!(*two, 2);
+        drop(two);
+
+        let elems: Vec<i32> = list.iter().rev().map(|elem| *elem).collect();
+        assert_eq!(elems, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        list.push_back(4);
+
+        let mut iter = list.iter_mut();
+        let mut first = iter.next().unwrap();
+        let mut last = iter.next_back().unwrap();
+        *first *= 10;
+        *last *= 10;
+        for mut elem in iter {
+            *elem += 1;
+        }
+        drop(first);
+        drop(last);
+        assert_eq!(drain(list), vec![10, 3, 4, 40]);
+    }
+
+    #[test]
+    fn try_pop() {
+        let mut list = List::new();
+        assert_eq!(list.try_pop_front(), Ok(None));
+        assert_eq!(list.try_pop_back(), Ok(None));
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        assert_eq!(list.try_pop_front(), Ok(Some(1)));
+        assert_eq!(list.try_pop_back(), Ok(Some(3)));
+        assert_eq!(list.try_pop_back(), Ok(Some(2)));
+        assert_eq!(list.try_pop_front(), Ok(None));
+    }
+
+    #[test]
+    fn try_pop_borrowed() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        // Leaking a guard leaves its node borrowed forever
+        mem::forget(list.peek_front());
+        assert_eq!(list.try_pop_front(), Err(ListError::Borrowed));
+        assert!(list.try_peek_front().is_ok());
+        assert_eq!(list.try_peek_front_mut().err(), Some(ListError::Borrowed));
+        assert_eq!(list.try_pop_back(), Ok(Some(3)));
+        // The front node is the back node's neighbour now
+        assert_eq!(list.try_pop_back(), Err(ListError::Borrowed));
+        assert_eq!(&*list.try_peek_back().unwrap().unwrap(), &2);
+        assert_eq!(&*list.try_peek_back_mut().unwrap().unwrap(), &mut 2);
+
+        mem::forget(list.peek_back_mut());
+        assert_eq!(list.try_peek_back().err(), Some(ListError::Borrowed));
+        assert_eq!(list.try_pop_back(), Err(ListError::Borrowed));
+        // The nodes can never be unlinked, so leak the list rather than panic in drop
+        mem::forget(list);
+    }
+
+    #[test]
+    fn try_handle_ops_borrowed() {
+        let mut list = List::new();
+        let one = list.push_back_handle(1);
+        let two = list.push_back_handle(2);
+        let three = list.push_back_handle(3);
+        let four = list.push_back_handle(4);
+        // Leaking a guard leaves its node borrowed forever
+        mem::forget(list.peek_front());
+
+        // The handle's node or a neighbour is borrowed
+        assert_eq!(list.try_remove(&one), Err(ListError::Borrowed));
+        assert_eq!(list.try_remove(&two), Err(ListError::Borrowed));
+        assert_eq!(list.try_insert_before(&two, 5).err(), Some((ListError::Borrowed, 5)));
+        assert_eq!(list.try_insert_after(&one, 6).err(), Some((ListError::Borrowed, 6)));
+        assert_eq!(list.try_move_to_front(&two), Err(ListError::Borrowed));
+        // The node and its neighbours are free, but it would be linked in before the head
+        assert_eq!(list.try_move_to_front(&three), Err(ListError::Borrowed));
+        assert_eq!(list.len(), 4);
+
+        let five = list.try_insert_after(&three, 5).unwrap().ok().unwrap();
+        assert_eq!(list.try_remove(&four), Ok(Some(4)));
+        assert_eq!(list.try_remove(&four), Ok(None));
+        assert_eq!(list.try_insert_before(&four, 7).unwrap().err(), Some(7));
+        assert_eq!(list.try_move_to_front(&four), Ok(false));
+        assert_eq!(list.try_remove(&five), Ok(Some(5)));
+        assert_eq!(list.len(), 3);
+        // The nodes can never be unlinked, so leak the list rather than panic in drop
+        mem::forget(list);
+    }
+
+    #[test]
+    fn try_peek() {
+        let mut list = List::new();
+        assert!(list.try_peek_front().unwrap().is_none());
+        assert!(list.try_peek_back_mut().unwrap().is_none());
+        list.push_front(1);
+        list.push_front(2);
+        *list.try_peek_front_mut().unwrap().unwrap() = 20;
+        assert_eq!(&*list.try_peek_front().unwrap().unwrap(), &20);
+        assert_eq!(&*list.try_peek_back().unwrap().unwrap(), &1);
+        assert_eq!(ListError::Borrowed.to_string(), "list node is still borrowed");
+    }
+
+    #[test]
+    fn no_leak_on_drop() {
+        let drops = Rc::new(Cell::new(0));
+        drop(counted_list(100, &drops));
+        assert_eq!(drops.get(), 100);
+
+        let mut list = counted_list(10, &drops);
+        list.pop_front();
+        list.pop_back();
+        assert_eq!(drops.get(), 102);
+        drop(list);
+        assert_eq!(drops.get(), 110);
+    }
+
+    #[test]
+    fn no_leak_from_iterators() {
+        let drops = Rc::new(Cell::new(0));
+        let mut iter = counted_list(10, &drops).into_iter();
+        iter.next();
+        iter.next_back();
+        drop(iter);
+        assert_eq!(drops.get(), 10);
+
+        let mut list = counted_list(10, &drops);
+        assert_eq!(list.iter().count(), 10);
+        assert_eq!(list.iter_mut().rev().count(), 10);
+        drop(list);
+        assert_eq!(drops.get(), 20);
+    }
+
+    #[test]
+    fn no_leak_from_handles() {
+        let drops = Rc::new(Cell::new(0));
+        let mut list = counted_list(3, &drops);
+        let handle = list.push_back_handle(Counted { drops: drops.clone(), panic_on_drop: false });
+        let other = list.push_front_handle(Counted { drops: drops.clone(), panic_on_drop: false });
+        assert!(list.move_to_front(&handle));
+        assert!(list.insert_after(&handle, Counted { drops: drops.clone(), panic_on_drop: false }).is_ok());
+        assert!(list.remove(&other).is_some());
+        assert_eq!(drops.get(), 1);
+        drop(list);
+        // Handles are weak, so they don't keep any nodes alive
+        assert!(handle.is_stale());
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn no_leak_on_panic() {
+        let drops = Rc::new(Cell::new(0));
+        let mut list = List::new();
+        list.push_back(Counted { drops: drops.clone(), panic_on_drop: true });
+        for _ in 0..4 {
+            list.push_back(Counted { drops: drops.clone(), panic_on_drop: false });
+        }
+        // The first element panics while the list is being dropped, which stops the list's
+        // Drop before it gets to the other nodes. They must still be freed while unwinding.
+        let result = panic::catch_unwind(AssertUnwindSafe(move || drop(list)));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+        list.push_front(2);
+        list.push_back(3);
+        let one = list.push_front_handle(1);
+        assert_eq!(list.len(), 3);
+        list.insert_after(&one, 10).ok().unwrap();
+        list.insert_before(&one, 0).ok().unwrap();
+        assert_eq!(list.len(), 5);
+        assert!(list.move_to_front(&one));
+        assert_eq!(list.remove(&one), Some(1));
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.try_pop_front(), Ok(Some(0)));
+        assert_eq!(list.len(), 2);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 2);
+        iter.next_back();
+        assert_eq!(iter.len(), 1);
+        iter.next();
+        assert_eq!(iter.len(), 0);
+        assert!(iter.next().is_none());
+        assert_eq!(list.iter_mut().rev().len(), 2);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(10));
+        assert_eq!(iter.len(), 1);
+    }
+}
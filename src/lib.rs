@@ -2,3 +2,153 @@ pub mod first;
 pub mod second;
 pub mod third;
 pub mod fourth;
+pub mod fifth;
+pub mod sixth;
+pub mod arena;
+pub mod treiber;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+/// A last-in, first-out collection.
+/// Lets code be generic over which of the lists it uses as a stack.
+pub trait Stack<T> {
+    /// Adds an element to the top of the stack.
+    fn push(&mut self, elem: T);
+
+    /// Removes the element from the top of the stack.
+    fn pop(&mut self) -> Option<T>;
+}
+
+/// A first-in, first-out collection.
+pub trait Queue<T> {
+    /// Adds an element to the back of the queue.
+    fn enqueue(&mut self, elem: T);
+
+    /// Removes the element from the front of the queue.
+    fn dequeue(&mut self) -> Option<T>;
+}
+
+/// A double-ended queue, which can be added to and removed from at either end.
+pub trait Deque<T> {
+    /// Adds an element to the front of the deque.
+    fn push_front(&mut self, elem: T);
+
+    /// Adds an element to the back of the deque.
+    fn push_back(&mut self, elem: T);
+
+    /// Removes the element from the front of the deque.
+    fn pop_front(&mut self) -> Option<T>;
+
+    /// Removes the element from the back of the deque.
+    fn pop_back(&mut self) -> Option<T>;
+}
+
+/// Conformance tests which every implementation of the traits above has to pass.
+#[cfg(test)]
+mod test {
+    use super::{Deque, Queue, Stack};
+
+    fn stack_conformance<S: Stack<i32> + Default>() {
+        let mut stack = S::default();
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+
+        // Long enough to overflow the stack if anything recurses per element
+        for elem in 0..100_000 {
+            stack.push(elem);
+        }
+        assert_eq!(stack.pop(), Some(99_999));
+    }
+
+    fn queue_conformance<Q: Queue<i32> + Default>() {
+        let mut queue = Q::default();
+        assert_eq!(queue.dequeue(), None);
+
+        queue.enqueue(1);
+        queue.enqueue(2);
+        queue.enqueue(3);
+        assert_eq!(queue.dequeue(), Some(1));
+        assert_eq!(queue.dequeue(), Some(2));
+
+        queue.enqueue(4);
+        assert_eq!(queue.dequeue(), Some(3));
+        assert_eq!(queue.dequeue(), Some(4));
+        assert_eq!(queue.dequeue(), None);
+
+        queue.enqueue(5);
+        assert_eq!(queue.dequeue(), Some(5));
+        assert_eq!(queue.dequeue(), None);
+
+        for elem in 0..100_000 {
+            queue.enqueue(elem);
+        }
+        assert_eq!(queue.dequeue(), Some(0));
+    }
+
+    fn deque_conformance<D: Deque<i32> + Default>() {
+        let mut deque = D::default();
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+
+        deque.push_front(2);
+        deque.push_front(1);
+        deque.push_back(3);
+        deque.push_back(4);
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_back(), Some(4));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_back(), Some(2));
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+
+        deque.push_back(5);
+        assert_eq!(deque.pop_front(), Some(5));
+        deque.push_front(6);
+        assert_eq!(deque.pop_back(), Some(6));
+
+        for elem in 0..100_000 {
+            deque.push_back(elem);
+        }
+        assert_eq!(deque.pop_front(), Some(0));
+        assert_eq!(deque.pop_back(), Some(99_999));
+    }
+
+    #[test]
+    fn stacks() {
+        stack_conformance::<crate::first::List<i32>>();
+        stack_conformance::<crate::second::List<i32>>();
+        stack_conformance::<crate::third::List<i32>>();
+        stack_conformance::<crate::third::RcList<i32>>();
+        stack_conformance::<crate::fourth::List<i32>>();
+        stack_conformance::<crate::sixth::List<i32>>();
+        stack_conformance::<crate::arena::List<i32>>();
+        stack_conformance::<crate::treiber::List<i32>>();
+    }
+
+    #[test]
+    fn queues() {
+        queue_conformance::<crate::third::queue::Queue<i32>>();
+        queue_conformance::<crate::fourth::List<i32>>();
+        queue_conformance::<crate::fifth::List<i32>>();
+        queue_conformance::<crate::sixth::List<i32>>();
+        queue_conformance::<crate::arena::List<i32>>();
+    }
+
+    #[test]
+    fn deques() {
+        deque_conformance::<crate::fourth::List<i32>>();
+        deque_conformance::<crate::sixth::List<i32>>();
+        deque_conformance::<crate::arena::List<i32>>();
+    }
+}
@@ -1,5 +1,14 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::mem::MaybeUninit;
+use std::ptr;
+
 pub struct List<T> {
     head: Link<T>,
+    len: usize,
+    pool: Pool<T>,
 }
 
 type Link<T> = Option<Box<Node<T>>>;
@@ -9,22 +18,90 @@ struct Node<T> {
     next: Link<T>,
 }
 
+/// A free list of node allocations which have been popped, so that push can reuse them
+/// instead of allocating. Holds at most `cap` nodes; any more are freed as usual.
+struct Pool<T> {
+    // The Vec starts empty and grows as nodes are recycled, so a large `cap` (even usize::MAX
+    // for an unbounded pool) costs nothing until that many nodes have actually been pooled.
+    free: Vec<Box<MaybeUninit<Node<T>>>>,
+    cap: usize,
+}
+
+impl<T> Pool<T> {
+    fn new(cap: usize) -> Self {
+        Pool { free: Vec::new(), cap }
+    }
+
+    /// Moves the node into a recycled allocation if there is one, or a new one otherwise.
+    fn alloc(&mut self, node: Node<T>) -> Box<Node<T>> {
+        match self.free.pop() {
+            Some(slot) => Box::write(slot, node),
+            None => Box::new(node),
+        }
+    }
+
+    /// Moves the node out of its allocation, keeping the allocation if there's room for it.
+    fn recycle(&mut self, node: Box<Node<T>>) -> Node<T> {
+        if self.free.len() == self.cap {
+            return *node;
+        }
+        let raw = Box::into_raw(node);
+        // SAFETY: raw came from a Box, so it points to an initialised node which we move out
+        // exactly once. The allocation is then treated as uninitialised memory, which has the
+        // same layout as the node, and will never be read before it is written again.
+        unsafe {
+            let node = ptr::read(raw);
+            self.free.push(Box::from_raw(raw.cast::<MaybeUninit<Node<T>>>()));
+            node
+        }
+    }
+}
+
 impl<T> List<T> {
     pub fn new() -> Self {
-        List{ head: None }
+        List{ head: None, len: 0, pool: Pool::new(0) }
+    }
+
+    /// Creates a list which keeps up to `cap` popped nodes aside and reuses them for later
+    /// pushes, so that a list which repeatedly grows and shrinks stops allocating once it has
+    /// warmed up. Only push and pop use the pool.
+    pub fn with_pool(cap: usize) -> Self {
+        List{ head: None, len: 0, pool: Pool::new(cap) }
+    }
+
+    /// Returns how many nodes are waiting in the pool to be reused.
+    pub fn pooled(&self) -> usize {
+        self.pool.free.len()
+    }
+
+    /// Frees every node waiting in the pool, and the pool's own storage. The pool will fill
+    /// up again as elements are popped.
+    pub fn shrink_to_fit(&mut self) {
+        self.pool.free = Vec::new();
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn push(&mut self, elem: T) {
-        let new_node = Box::new(Node {
+        let new_node = self.pool.alloc(Node {
             elem,
             next: self.head.take()
         });
         self.head = Some(new_node);
+        self.len += 1;
     }
 
     pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|node| {
+            let node = self.pool.recycle(node);
             self.head = node.next;
+            self.len -= 1;
             node.elem
         })
     }
@@ -37,16 +114,242 @@ impl<T> List<T> {
         self.head.as_mut().map(|node| &mut node.elem)
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.head.as_deref(), len: self.len }
     }
 
-    pub fn iter(&self) -> Iter<T> {
-        Iter { next: self.head.as_deref()}
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { next: self.head.as_deref_mut(), len: self.len }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
-        IterMut { next: self.head.as_deref_mut()}
+    /// Sorts the list in ascending order. See sort_by.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Sorts the list with a key extraction function. See sort_by.
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut key: F) {
+        self.sort_by(|a, b| key(a).cmp(&key(b)));
+    }
+
+    /// Sorts the list with a comparator function, keeping equal elements in their original order.
+    /// This is a bottom-up merge sort which relinks the existing nodes instead of moving
+    /// elements, so it takes O(n log n) time and O(1) extra memory however large the
+    /// elements are. Each pass merges neighbouring sorted runs into runs twice as long,
+    /// until a single run is left.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        let mut chains = MergeGuard::new(self);
+        let mut width = 1;
+        loop {
+            chains.rest = chains.merged.take();
+            let mut tail = &mut chains.merged;
+            let mut merges = 0;
+            while chains.rest.is_some() {
+                chains.left = chains.rest.take();
+                chains.right = List::split_link(&mut chains.left, width);
+                chains.rest = List::split_link(&mut chains.right, width);
+                tail = List::merge_links(tail, &mut chains.left, &mut chains.right, &mut compare);
+                merges += 1;
+            }
+            if merges <= 1 {
+                break;
+            }
+            width *= 2;
+        }
+    }
+
+    /// Merge sort is already as fast as an unstable sort can be on a linked list, so this
+    /// is the same as sort_by. It's here so code written against slices still works.
+    pub fn sort_unstable_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        self.sort_by(compare);
+    }
+
+    /// Merges another sorted list into this sorted list, relinking the nodes of both,
+    /// so that the result is sorted. Elements of this list come before equal elements of
+    /// the other one.
+    pub fn merge_sorted(&mut self, mut other: List<T>)
+    where
+        T: Ord,
+    {
+        let mut chains = MergeGuard::new(self);
+        chains.left = chains.merged.take();
+        chains.right = other.head.take();
+        other.len = 0;
+        List::merge_links(&mut chains.merged, &mut chains.left, &mut chains.right, &mut T::cmp);
+    }
+
+    /// Detaches everything after the first `n` nodes from the given link and returns it.
+    fn split_link(mut link: &mut Link<T>, n: usize) -> Link<T> {
+        for _ in 0..n {
+            match link {
+                Some(node) => link = &mut node.next,
+                None => return None,
+            }
+        }
+        link.take()
+    }
+
+    /// Moves the nodes of two sorted chains onto the empty link `tail`, preferring `left`
+    /// when elements are equal, and returns the empty link after the merged nodes.
+    /// The chains are borrowed rather than owned so that if `compare` panics, the nodes
+    /// not yet merged are still where the caller's MergeGuard can find them.
+    fn merge_links<'a, F: FnMut(&T, &T) -> Ordering>(
+        mut tail: &'a mut Link<T>,
+        left: &mut Link<T>,
+        right: &mut Link<T>,
+        compare: &mut F,
+    ) -> &'a mut Link<T> {
+        loop {
+            let take_right = match (&*left, &*right) {
+                (Some(left), Some(right)) => compare(&right.elem, &left.elem) == Ordering::Less,
+                (Some(_), None) | (None, Some(_)) => {
+                    // Only one chain is left, so it can be attached as a whole
+                    *tail = left.take().or(right.take());
+                    return List::last_link(tail);
+                }
+                (None, None) => return tail,
+            };
+            let source = if take_right { &mut *right } else { &mut *left };
+            let mut node = source.take().unwrap();
+            *source = node.next.take();
+            tail = &mut tail.insert(node).next;
+        }
+    }
+
+    /// Follows the links from the given one to the empty link after the last node,
+    /// where new nodes can be appended.
+    fn last_link(mut link: &mut Link<T>) -> &mut Link<T> {
+        while let Some(node) = link {
+            link = &mut node.next;
+        }
+        link
+    }
+
+    /// Returns a cursor positioned on the "ghost" before the first element.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { current: None, next: Some(&mut self.head), len: &mut self.len, before: 0 }
+    }
+}
+
+/// Holds the chains of nodes detached from a list while it's sorted or merged into, and
+/// links them all back onto the list when dropped. Normally only `merged` is left by then,
+/// but if the comparator panics the unwind drops this guard with nodes in every chain, so
+/// none of them are lost or freed recursively, and the list's length is counted again.
+struct MergeGuard<'a, T> {
+    list: &'a mut List<T>,
+    merged: Link<T>,
+    left: Link<T>,
+    right: Link<T>,
+    rest: Link<T>,
+}
+
+impl<'a, T> MergeGuard<'a, T> {
+    /// Detaches the list's nodes into `merged`.
+    fn new(list: &'a mut List<T>) -> Self {
+        let merged = list.head.take();
+        MergeGuard { list, merged, left: None, right: None, rest: None }
+    }
+}
+
+impl<T> Drop for MergeGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut len = 0;
+        for mut chain in [self.rest.take(), self.right.take(), self.left.take(), self.merged.take()] {
+            let mut link = &mut chain;
+            while let Some(node) = link {
+                len += 1;
+                link = &mut node.next;
+            }
+            *link = self.list.head.take();
+            self.list.head = chain;
+        }
+        self.list.len = len;
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> crate::Stack<T> for List<T> {
+    fn push(&mut self, elem: T) {
+        List::push(self, elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        List::pop(self)
+    }
+}
+
+// All of the traits which look at every element walk the list with Iter, rather than
+// recursing down the nodes, so that they don't overflow the stack on long lists.
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+/// Lists are ordered lexicographically, comparing elements from the head onwards.
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Start with the length so that e.g. ([1], [2, 3]) and ([1, 2], [3]) hash differently
+        state.write_usize(self.len);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+/// Collecting keeps the iterator's order, so the first element ends up at the head.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+/// Appends the elements after the last element of the list, keeping their order.
+/// This has to walk the list to find its end, so it takes O(n) on top of the new elements.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut link = List::last_link(&mut self.head);
+        for elem in iter {
+            link = &mut link.insert(Box::new(Node { elem, next: None })).next;
+            self.len += 1;
+        }
     }
 }
 
@@ -61,15 +364,42 @@ impl<T> Drop for List<T> {
 
 pub struct IntoIter<T>(List<T>);
 
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
 impl<T> Iterator for IntoIter<T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
 }
 
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -78,13 +408,32 @@ impl<'a, T> Iterator for Iter<'a, T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.next.map(|node| {
             self.next = node.next.as_deref();
+            self.len -= 1;
             &node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
 pub struct IterMut<'a, T> {
     next: Option<&'a mut Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
 }
 
 impl<'a, T> Iterator for IterMut<'a, T> {
@@ -93,15 +442,126 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.next.take().map(|node| {
             self.next = node.next.as_deref_mut();
+            self.len -= 1;
             &mut node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// A cursor which can walk the list from front to back and edit it at the current position.
+/// Because the list is singly-linked the cursor can only move forward. It starts on a "ghost"
+/// position before the first element, and moving past the last element parks it on a ghost
+/// position after the last element, where it stays.
+/// All edits happen directly after the cursor, so the element under the cursor is never moved.
+pub struct CursorMut<'a, T> {
+    current: Option<&'a mut T>,
+    // The link following the current position. Only None while the cursor is being moved.
+    next: Option<&'a mut Link<T>>,
+    // The list's length, kept up to date as elements are added and removed.
+    len: &'a mut usize,
+    // The number of elements up to and including the current position.
+    before: usize,
 }
 
+impl<'a, T> CursorMut<'a, T> {
+    /// Returns the index of the current element, or None if the cursor is on a ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.is_some().then(|| self.before - 1)
+    }
+
+    /// Moves the cursor to the next element. Does nothing once the cursor is past the end.
+    pub fn move_next(&mut self) {
+        if let Some(link) = self.next.take() {
+            if link.is_none() {
+                // Past the last element, park on the trailing ghost.
+                self.current = None;
+                self.next = Some(link);
+                return;
+            }
+            let Node { elem, next } = &mut **link.as_mut().unwrap();
+            self.before += 1;
+            self.current = Some(elem);
+            self.next = Some(next);
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.as_deref_mut()
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.link().as_deref_mut().map(|node| &mut node.elem)
+    }
+
+    /// Inserts an element directly after the cursor in O(1).
+    pub fn insert_after(&mut self, elem: T) {
+        let link = self.link();
+        let new_node = Box::new(Node {
+            elem,
+            next: link.take(),
+        });
+        *link = Some(new_node);
+        *self.len += 1;
+    }
+
+    /// Removes and returns the element directly after the cursor in O(1).
+    pub fn remove_next(&mut self) -> Option<T> {
+        let link = self.link();
+        let node = link.take()?;
+        *link = node.next;
+        *self.len -= 1;
+        Some(node.elem)
+    }
+
+    /// Detaches every element after the cursor and returns them as a new list in O(1).
+    pub fn split_after(&mut self) -> List<T> {
+        let head = self.link().take();
+        let len = *self.len - self.before;
+        *self.len = self.before;
+        let mut list = List::new();
+        list.head = head;
+        list.len = len;
+        list
+    }
+
+    /// Moves all elements of `other` into this list directly after the cursor in O(m), where
+    /// m is the length of `other`. Lists don't track their tail, so the last node of `other`
+    /// has to be found by walking it before the rest of this list can be reattached.
+    pub fn splice_after(&mut self, mut other: List<T>) {
+        let mut spliced = match other.head.take() {
+            Some(head) => head,
+            None => return,
+        };
+        let link = self.link();
+        let mut tail = &mut spliced;
+        while tail.next.is_some() {
+            tail = tail.next.as_mut().unwrap();
+        }
+        tail.next = link.take();
+        *link = Some(spliced);
+        *self.len += other.len;
+    }
+
+    fn link(&mut self) -> &mut Link<T> {
+        self.next.as_deref_mut().unwrap()
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::List;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+    use std::panic::{self, AssertUnwindSafe};
 
     #[test]
     fn basics() {
@@ -126,6 +586,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::option_map_unit_fn)]
     fn peek() {
         let mut list = List::new();
         assert_eq!(list.peek(), None);
@@ -179,4 +640,387 @@ mod test {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
-}
\ No newline at end of file
+
+    fn collect(list: &List<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn cursor_walk() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 3));
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(2));
+        if let Some(value) = cursor.current() {
+            *value = 10;
+        }
+        assert_eq!(cursor.peek_next(), None);
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(collect(&list), vec![3, 2, 10]);
+    }
+
+    #[test]
+    fn cursor_insert_remove() {
+        let mut list = List::new();
+        list.push(3);
+        list.push(1);
+
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(0);
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_after(2);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.remove_next(), Some(2));
+        assert_eq!(cursor.remove_next(), Some(3));
+        assert_eq!(cursor.remove_next(), None);
+        cursor.insert_after(4);
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_after(5);
+        assert_eq!(collect(&list), vec![0, 1, 4, 5]);
+    }
+
+    #[test]
+    fn cursor_index_after_ghost_insert() {
+        let mut list = List::new();
+        list.push(2);
+        list.push(1);
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        // Inserting on the trailing ghost appends, and moving onto it counts the elements passed
+        cursor.insert_after(3);
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(collect(&list), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn cursor_split_splice() {
+        let mut list = List::new();
+        for elem in [5, 4, 3, 2, 1] {
+            list.push(elem);
+        }
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let rest = cursor.split_after();
+        assert_eq!(collect(&rest), vec![3, 4, 5]);
+
+        let mut other = List::new();
+        other.push(20);
+        other.push(10);
+        cursor.splice_after(other);
+        cursor.splice_after(List::new());
+        cursor.move_next();
+        cursor.move_next();
+        cursor.splice_after(rest);
+        assert_eq!(cursor.current(), Some(&mut 20));
+        assert_eq!(collect(&list), vec![1, 2, 10, 20, 3, 4, 5]);
+
+        let mut cursor = list.cursor_mut();
+        let all = cursor.split_after();
+        assert_eq!(collect(&all), vec![1, 2, 10, 20, 3, 4, 5]);
+        assert_eq!(list.peek(), None);
+    }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.peek(), Some(&1));
+        assert_eq!(collect(&list), vec![1, 2, 3]);
+        list.extend(4..=5);
+        assert_eq!(collect(&list), vec![1, 2, 3, 4, 5]);
+
+        let mut empty = List::default();
+        empty.extend(vec![1]);
+        assert_eq!(collect(&empty), vec![1]);
+    }
+
+    #[test]
+    fn borrowed_into_iter() {
+        let mut list: List<i32> = (1..=3).collect();
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        let mut total = 0;
+        for elem in &list {
+            total += elem;
+        }
+        assert_eq!(total, 60);
+    }
+
+    #[test]
+    fn clone_eq_ord() {
+        let list: List<i32> = (1..=3).collect();
+        let copy = list.clone();
+        assert_eq!(list, copy);
+        assert_eq!(format!("{:?}", copy), "[1, 2, 3]");
+
+        let shorter: List<i32> = (1..=2).collect();
+        let bigger: List<i32> = vec![1, 3].into_iter().collect();
+        assert_ne!(list, shorter);
+        assert!(shorter < list);
+        assert!(list < bigger);
+        assert_eq!(list.cmp(&copy), std::cmp::Ordering::Equal);
+        assert_eq!(List::<f64>::new().partial_cmp(&List::new()), Some(std::cmp::Ordering::Equal));
+    }
+
+    #[test]
+    fn hash() {
+        let list: List<i32> = (1..=3).collect();
+        assert_eq!(hash_of(&list), hash_of(&list.clone()));
+
+        let split = (List::from_iter([1]), List::from_iter([2, 3]));
+        let other_split = (List::from_iter([1, 2]), List::from_iter([3]));
+        assert_ne!(hash_of(&split), hash_of(&other_split));
+
+        let set: HashSet<List<i32>> = [list.clone(), list, List::new()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn long_list_traits() {
+        // Recursive implementations would overflow the stack on lists this long
+        let list: List<i32> = (0..1_000_000).collect();
+        let copy = list.clone();
+        assert!(list == copy);
+        assert!(list <= copy);
+        assert_eq!(hash_of(&list), hash_of(&copy));
+    }
+
+    #[test]
+    fn len() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.len(), 3);
+        list.push(0);
+        assert_eq!(list.len(), 4);
+        list.pop();
+        list.extend([4, 5]);
+        assert_eq!(list.len(), 5);
+        assert!(!list.is_empty());
+
+        let mut iter = list.iter();
+        iter.next();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(list.iter_mut().skip(2).len(), 3);
+        let mut iter = list.clone().into_iter();
+        assert_eq!(iter.len(), 5);
+        iter.by_ref().for_each(drop);
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+
+        // Lists with equal elements but different lengths
+        assert_ne!(list, list.iter().copied().take(4).collect());
+        while list.pop().is_some() {}
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn cursor_len() {
+        let mut list: List<i32> = (1..=5).collect();
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(0);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_next(), Some(2));
+        let rest = cursor.split_after();
+        assert_eq!(rest.len(), 3);
+        cursor.splice_after(rest);
+        cursor.move_next();
+        let rest = cursor.split_after();
+        assert_eq!(rest.len(), 2);
+        assert_eq!(collect(&rest), vec![4, 5]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(collect(&list), vec![0, 1, 3]);
+
+        let mut cursor = list.cursor_mut();
+        for _ in 0..4 {
+            cursor.move_next();
+        }
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(6);
+        assert_eq!(cursor.split_after().len(), 1);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn sort() {
+        let mut list: List<i32> = vec![5, 3, 9, 1, 1, 8, 2, 7].into_iter().collect();
+        list.sort();
+        assert_eq!(collect(&list), vec![1, 1, 2, 3, 5, 7, 8, 9]);
+        assert_eq!(list.len(), 8);
+
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(collect(&list), vec![9, 8, 7, 5, 3, 2, 1, 1]);
+        list.sort_unstable_by(|a, b| a.cmp(b));
+        assert_eq!(collect(&list), vec![1, 1, 2, 3, 5, 7, 8, 9]);
+
+        let mut empty: List<i32> = List::new();
+        empty.sort();
+        assert!(empty.is_empty());
+        let mut single: List<i32> = (1..=1).collect();
+        single.sort();
+        assert_eq!(collect(&single), vec![1]);
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let mut list: List<(i32, char)> =
+            vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e'), (2, 'f')].into_iter().collect();
+        list.sort_by_key(|&(key, _)| key);
+        let order: String = list.iter().map(|&(_, name)| name).collect();
+        assert_eq!(order, "ebdacf");
+    }
+
+    #[test]
+    fn sort_relinks_nodes() {
+        let mut list: List<String> = ["b", "c", "a"].iter().map(|s| s.to_string()).collect();
+        let before = list.iter().find(|s| *s == "a").unwrap() as *const String;
+        list.sort();
+        assert!(std::ptr::eq(list.peek().unwrap(), before));
+    }
+
+    #[test]
+    fn sort_long_list() {
+        let mut list: List<u32> = (0..100_000u32).map(|n| n.wrapping_mul(2_654_435_761) % 1000).collect();
+        list.sort();
+        assert_eq!(list.len(), 100_000);
+        assert!(list.iter().zip(list.iter().skip(1)).all(|(a, b)| a <= b));
+    }
+
+    #[test]
+    fn sort_panic_keeps_elements() {
+        fn sort_panicking_after(list: &mut List<u32>, calls: usize) {
+            let mut count = 0;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                list.sort_by(|a, b| {
+                    count += 1;
+                    assert!(count < calls, "comparator panicked");
+                    a.cmp(b)
+                })
+            }));
+            assert!(result.is_err());
+        }
+
+        let mut list: List<u32> = (0..10).rev().collect();
+        sort_panicking_after(&mut list, 5);
+        assert_eq!(list.len(), 10);
+        let mut elems: Vec<u32> = list.iter().copied().collect();
+        elems.sort();
+        assert_eq!(elems, (0..10).collect::<Vec<_>>());
+
+        // Long chains left detached by the panic are put back rather than dropped recursively
+        let mut list: List<u32> = (0..1_000_000).collect();
+        sort_panicking_after(&mut list, 1);
+        assert_eq!(list.len(), 1_000_000);
+        assert_eq!(list.iter().count(), 1_000_000);
+
+        // Comparing against 13 panics, partway through merging the two lists
+        #[derive(PartialEq, Eq)]
+        struct Unlucky(u32);
+        impl PartialOrd for Unlucky {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Unlucky {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                assert!(self.0 != 13 && other.0 != 13, "comparator panicked");
+                self.0.cmp(&other.0)
+            }
+        }
+        let mut list: List<Unlucky> = [1, 5, 20].into_iter().map(Unlucky).collect();
+        let other: List<Unlucky> = [2, 13, 30].into_iter().map(Unlucky).collect();
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| list.merge_sorted(other))).is_err());
+        assert_eq!(list.len(), 6);
+        let mut elems: Vec<u32> = list.iter().map(|Unlucky(n)| *n).collect();
+        elems.sort();
+        assert_eq!(elems, vec![1, 2, 5, 13, 20, 30]);
+    }
+
+    #[test]
+    fn merge_sorted() {
+        let mut list: List<i32> = vec![1, 4, 4, 9].into_iter().collect();
+        list.merge_sorted(vec![0, 4, 5, 10, 11].into_iter().collect());
+        assert_eq!(collect(&list), vec![0, 1, 4, 4, 4, 5, 9, 10, 11]);
+        assert_eq!(list.len(), 9);
+
+        list.merge_sorted(List::new());
+        assert_eq!(list.len(), 9);
+        let mut empty = List::new();
+        empty.merge_sorted(list);
+        assert_eq!(empty.len(), 9);
+        assert_eq!(empty.peek(), Some(&0));
+    }
+
+    #[test]
+    fn pool() {
+        let mut list = List::with_pool(2);
+        list.push(String::from("a"));
+        list.push(String::from("b"));
+        list.push(String::from("c"));
+        let node = list.peek().unwrap() as *const String;
+        assert_eq!(list.pop().as_deref(), Some("c"));
+        assert_eq!(list.pooled(), 1);
+        list.push(String::from("d"));
+        // The popped node was reused for the new element
+        assert!(std::ptr::eq(list.peek().unwrap(), node));
+        assert_eq!(list.pooled(), 0);
+
+        while list.pop().is_some() {}
+        assert_eq!(list.pooled(), 2);
+        list.shrink_to_fit();
+        assert_eq!(list.pooled(), 0);
+        list.push(String::from("e"));
+        assert_eq!(list.pop().as_deref(), Some("e"));
+        assert_eq!(list.pooled(), 1);
+        list.push(String::from("f"));
+        // Pooled nodes and remaining elements are both freed on drop
+    }
+
+    #[test]
+    fn unbounded_pool() {
+        let mut list = List::with_pool(usize::MAX);
+        for elem in 0..1000 {
+            list.push(elem);
+        }
+        while list.pop().is_some() {}
+        assert_eq!(list.pooled(), 1000);
+    }
+
+    #[test]
+    fn no_pool() {
+        let mut list = List::new();
+        list.push(1);
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pooled(), 0);
+    }
+}
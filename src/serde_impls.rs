@@ -0,0 +1,219 @@
+//! Serialize and Deserialize implementations for every list, behind the `serde` feature.
+//! Lists are written as a sequence in the order their iterators visit the elements (front to
+//! back), not the order they were pushed in, so a list always reads back in the same order.
+//! Deserializing gathers the elements into a Vec first, since several of the lists can only be
+//! built from the back.
+
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::third::random_access::RandomAccessList;
+use crate::third::SharedPointerKind;
+use crate::{arena, fifth, first, fourth, second, sixth, third};
+
+impl<T: Serialize> Serialize for first::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for first::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        let mut list = first::List::new();
+        for elem in elems.into_iter().rev() {
+            list.push(elem);
+        }
+        Ok(list)
+    }
+}
+
+impl<T: Serialize> Serialize for second::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for second::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<T>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+impl<T: Serialize, P: SharedPointerKind> Serialize for third::List<T, P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+/// Builds a fresh chain of nodes which isn't shared with any other list.
+impl<'de, T: Deserialize<'de>, P: SharedPointerKind> Deserialize<'de> for third::List<T, P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<T>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+impl<T: Serialize> Serialize for RandomAccessList<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for RandomAccessList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<T>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+impl<T: Serialize> Serialize for fourth::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for elem in self.iter() {
+            seq.serialize_element(&*elem)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for fourth::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut list = fourth::List::new();
+        for elem in Vec::<T>::deserialize(deserializer)? {
+            list.push_back(elem);
+        }
+        Ok(list)
+    }
+}
+
+impl<T: Serialize> Serialize for fifth::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for fifth::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut list = fifth::List::new();
+        for elem in Vec::<T>::deserialize(deserializer)? {
+            list.push(elem);
+        }
+        Ok(list)
+    }
+}
+
+impl<T: Serialize> Serialize for sixth::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for sixth::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<T>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+impl<T: Serialize> Serialize for arena::List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for arena::List<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        let mut list = arena::List::with_capacity(elems.len());
+        for elem in elems {
+            list.push_back(elem);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{arena, fifth, first, fourth, second, sixth, third};
+
+    #[test]
+    fn first() {
+        let mut list = first::List::new();
+        list.push(3);
+        list.push(2);
+        list.push(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let mut list: first::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn second() {
+        let list: second::List<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, r#"["a","b"]"#);
+        assert_eq!(serde_json::from_str::<second::List<String>>(&json).unwrap(), list);
+        assert!(serde_json::from_str::<second::List<i32>>("[]").unwrap().is_empty());
+        assert!(serde_json::from_str::<second::List<i32>>(r#"["a"]"#).is_err());
+    }
+
+    #[test]
+    fn third() {
+        let base = third::List::new().prepend(3);
+        let list = base.prepend(2).prepend(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let read: third::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, list);
+        // The deserialized list has its own nodes rather than sharing any
+        assert!(!std::ptr::eq(read.tail().tail().head().unwrap(), base.head().unwrap()));
+
+        let read: third::RcList<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        let read: third::random_access::RandomAccessList<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.get(2), Some(&3));
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    }
+
+    #[test]
+    fn fourth() {
+        let mut list = fourth::List::new();
+        list.push_front(2);
+        list.push_back(3);
+        list.push_front(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let read: fourth::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn fifth_and_sixth() {
+        let mut queue = fifth::List::new();
+        queue.push(1);
+        queue.push(2);
+        let json = serde_json::to_string(&queue).unwrap();
+        assert_eq!(json, "[1,2]");
+        let read: fifth::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+
+        let deque: sixth::List<i32> = (1..=3).collect();
+        let json = serde_json::to_string(&deque).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        assert_eq!(serde_json::from_str::<sixth::List<i32>>(&json).unwrap(), deque);
+    }
+
+    #[test]
+    fn arena() {
+        let mut list = arena::List::new();
+        list.push_back(2);
+        list.push_front(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2]");
+        let read: arena::List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+}
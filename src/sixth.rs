@@ -0,0 +1,639 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+/// A doubly-linked deque built on raw pointers, in the style of std::collections::LinkedList.
+/// Unlike fourth::List, it hands out plain references to its elements and never panics
+/// because of a borrow which is still alive: the borrow checker tracks them instead.
+/// Links are NonNull rather than *mut so that the list is covariant in T (a List<&'static str>
+/// can be used where a List<&'a str> is expected), and PhantomData<T> tells the compiler that
+/// the list owns and drops values of type T.
+pub struct List<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _owns: PhantomData<T>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+// The list owns its elements, so it can be sent or shared exactly when they can.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None, tail: None, len: 0, _owns: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let new_head = Node::new(elem);
+        // SAFETY: the nodes linked from the list are always live and owned by it.
+        unsafe {
+            match self.head {
+                Some(old_head) => {
+                    (*old_head.as_ptr()).prev = Some(new_head);
+                    (*new_head.as_ptr()).next = Some(old_head);
+                }
+                None => self.tail = Some(new_head),
+            }
+        }
+        self.head = Some(new_head);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let new_tail = Node::new(elem);
+        // SAFETY: as for push_front.
+        unsafe {
+            match self.tail {
+                Some(old_tail) => {
+                    (*old_tail.as_ptr()).next = Some(new_tail);
+                    (*new_tail.as_ptr()).prev = Some(old_tail);
+                }
+                None => self.head = Some(new_tail),
+            }
+        }
+        self.tail = Some(new_tail);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|old_head| {
+            // SAFETY: the head came from Box::leak in Node::new and is only freed here or in
+            // pop_back, after it has been unlinked from the list.
+            let old_head = unsafe { Box::from_raw(old_head.as_ptr()) };
+            self.head = old_head.next;
+            match self.head {
+                // SAFETY: as for push_front.
+                Some(new_head) => unsafe { (*new_head.as_ptr()).prev = None },
+                None => self.tail = None,
+            }
+            self.len -= 1;
+            old_head.elem
+        })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|old_tail| {
+            // SAFETY: as for pop_front.
+            let old_tail = unsafe { Box::from_raw(old_tail.as_ptr()) };
+            self.tail = old_tail.prev;
+            match self.tail {
+                // SAFETY: as for push_front.
+                Some(new_tail) => unsafe { (*new_tail.as_ptr()).next = None },
+                None => self.head = None,
+            }
+            self.len -= 1;
+            old_tail.elem
+        })
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        // SAFETY: the node is live, and the borrow of self keeps it that way.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as for peek_front, and the mutable borrow of self makes the reference unique.
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        // SAFETY: as for peek_front.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as for peek_front_mut.
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    /// Removes every element from the list.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn contains(&self, elem: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|other| other == elem)
+    }
+
+    /// Moves every element of `other` onto the back of this list in O(1), leaving `other` empty.
+    pub fn append(&mut self, other: &mut List<T>) {
+        match (self.tail, other.head.take()) {
+            (Some(tail), Some(other_head)) => {
+                // SAFETY: both nodes are live, and other gives up ownership of its nodes here.
+                unsafe {
+                    (*tail.as_ptr()).next = Some(other_head);
+                    (*other_head.as_ptr()).prev = Some(tail);
+                }
+                self.tail = other.tail.take();
+                self.len += mem::replace(&mut other.len, 0);
+            }
+            (None, other_head) => {
+                other.head = other_head;
+                mem::swap(self, other);
+            }
+            (Some(_), None) => {}
+        }
+    }
+
+    /// Splits the list in two at the given index, returning everything from `at` onwards.
+    /// Walks from whichever end of the list is closer, so this takes O(min(at, len - at)).
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> List<T> {
+        assert!(at <= self.len, "cannot split off at an index past the end of the list");
+        if at == 0 {
+            return mem::take(self);
+        }
+        if at == self.len {
+            return List::new();
+        }
+
+        // Find the node which will become this list's tail
+        let mut new_tail = if at - 1 < self.len / 2 {
+            let mut node = self.head.unwrap();
+            for _ in 0..at - 1 {
+                // SAFETY: there are more than `at` nodes, so every link followed here is Some.
+                node = unsafe { (*node.as_ptr()).next.unwrap() };
+            }
+            node
+        } else {
+            let mut node = self.tail.unwrap();
+            for _ in at - 1..self.len - 1 {
+                // SAFETY: as above, walking backwards.
+                node = unsafe { (*node.as_ptr()).prev.unwrap() };
+            }
+            node
+        };
+
+        // SAFETY: new_tail is live and has a next node, since at < len.
+        let other_head = unsafe {
+            let other_head = new_tail.as_mut().next.take().unwrap();
+            (*other_head.as_ptr()).prev = None;
+            other_head
+        };
+        let other = List {
+            head: Some(other_head),
+            tail: self.tail,
+            len: self.len - at,
+            _owns: PhantomData,
+        };
+        self.tail = Some(new_tail);
+        self.len = at;
+        other
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { front: self.head, back: self.tail, len: self.len, _list: PhantomData }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { front: self.head, back: self.tail, len: self.len, _list: PhantomData }
+    }
+}
+
+impl<T> Node<T> {
+    /// Allocates a new unlinked node. The list is responsible for freeing it with Box::from_raw.
+    fn new(elem: T) -> NonNull<Self> {
+        NonNull::from(Box::leak(Box::new(Node { elem, next: None, prev: None })))
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> crate::Stack<T> for List<T> {
+    fn push(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+impl<T> crate::Queue<T> for List<T> {
+    fn enqueue(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+impl<T> crate::Deque<T> for List<T> {
+    fn push_front(&mut self, elem: T) {
+        List::push_front(self, elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        List::push_back(self, elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        List::pop_front(self)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        List::pop_back(self)
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Iterates over the list from either end. Rather than checking whether the ends have met,
+/// it counts down the number of elements left, since the list knows its length.
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _list: PhantomData<&'a T>,
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|node| {
+            self.len -= 1;
+            // SAFETY: the node is live while the list is borrowed for 'a.
+            unsafe {
+                self.front = (*node.as_ptr()).next;
+                &(*node.as_ptr()).elem
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|node| {
+            self.len -= 1;
+            // SAFETY: as for next.
+            unsafe {
+                self.back = (*node.as_ptr()).prev;
+                &(*node.as_ptr()).elem
+            }
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _list: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|node| {
+            self.len -= 1;
+            // SAFETY: the node is live while the list is mutably borrowed for 'a, and the
+            // length check stops either end from handing out a node twice.
+            unsafe {
+                self.front = (*node.as_ptr()).next;
+                &mut (*node.as_ptr()).elem
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|node| {
+            self.len -= 1;
+            // SAFETY: as for next.
+            unsafe {
+                self.back = (*node.as_ptr()).prev;
+                &mut (*node.as_ptr()).elem
+            }
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    fn list_from(elems: &[i32]) -> List<i32> {
+        elems.iter().copied().collect()
+    }
+
+    fn assert_properties<T: Send + Sync>() {}
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        list.push_back(4);
+        assert_eq!(list.len(), 4);
+
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        list.push_back(5);
+        assert_eq!(list.pop_front(), Some(5));
+        assert_eq!(list.pop_back(), None);
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek_front(), None);
+        assert_eq!(list.peek_back_mut(), None);
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        assert_eq!(list.peek_front(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&3));
+        *list.peek_front_mut().unwrap() = 10;
+        *list.peek_back_mut().unwrap() = 30;
+        assert_eq!(list, list_from(&[10, 2, 30]));
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list = list_from(&[1, 2, 3, 4, 5]);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next_back(), Some(&mut 50));
+        assert_eq!(iter.next(), Some(&mut 10));
+        assert_eq!(iter.count(), 3);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next_back(), Some(50));
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.collect::<Vec<_>>(), vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn clear_and_contains() {
+        let mut list = list_from(&[1, 2, 3]);
+        assert!(list.contains(&2));
+        assert!(!list.contains(&4));
+        list.clear();
+        assert!(list.is_empty());
+        assert!(!list.contains(&2));
+        list.push_back(4);
+        assert_eq!(list, list_from(&[4]));
+    }
+
+    #[test]
+    fn append() {
+        let mut list = list_from(&[1, 2]);
+        let mut other = list_from(&[3, 4]);
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list, list_from(&[1, 2, 3, 4]));
+        assert_eq!(list.peek_back(), Some(&4));
+
+        list.append(&mut List::new());
+        assert_eq!(list.len(), 4);
+
+        let mut empty = List::new();
+        empty.append(&mut list);
+        assert!(list.is_empty());
+        assert_eq!(empty, list_from(&[1, 2, 3, 4]));
+        assert_eq!(empty.iter().rev().copied().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn split_off() {
+        for at in 0..=6 {
+            let elems = [1, 2, 3, 4, 5, 6];
+            let mut list = list_from(&elems);
+            let other = list.split_off(at);
+            assert_eq!(list, list_from(&elems[..at]));
+            assert_eq!(other, list_from(&elems[at..]));
+            assert_eq!(list.iter().rev().count(), at);
+            assert_eq!(other.iter().rev().count(), 6 - at);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_past_end() {
+        list_from(&[1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn traits() {
+        let list = list_from(&[1, 2, 3]);
+        let copy = list.clone();
+        assert_eq!(list, copy);
+        assert_ne!(list, list_from(&[1, 2]));
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        let mut extended = List::default();
+        extended.extend(copy);
+        assert_eq!(extended, list);
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_properties::<List<i32>>();
+        assert_properties::<super::Iter<'_, i32>>();
+        assert_properties::<super::IterMut<'_, i32>>();
+        assert_properties::<super::IntoIter<i32>>();
+    }
+
+    #[test]
+    fn covariance() {
+        fn shorten<'a>(list: List<&'static str>) -> List<&'a str> {
+            list
+        }
+        fn shorten_iter<'i, 'a>(iter: super::Iter<'i, &'static str>) -> super::Iter<'i, &'a str> {
+            iter
+        }
+        let list = shorten(List::new());
+        assert!(shorten_iter(list.iter()).next().is_none());
+    }
+}
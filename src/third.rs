@@ -1,36 +1,171 @@
-// We'll use atomic reference counters to keep track of values which are in multiple lists.
+// We'll use reference counters to keep track of values which are in multiple lists.
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::ops::Deref;
+use std::rc::Rc;
 use std::sync::Arc;
 
-pub struct List<T> {
-    head: Link<T>
+use builder::ListBuilder;
+
+pub mod atomic;
+pub mod builder;
+pub mod diagnostics;
+pub mod queue;
+pub mod random_access;
+
+/// The reference-counted pointer that a list's nodes are shared through. Lists use ArcK by
+/// default so that versions can be shared between threads, but single-threaded code can use
+/// RcK instead to avoid paying for atomic reference counting.
+pub trait SharedPointerKind {
+    type Pointer<T>: Deref<Target = T> + Clone;
+
+    fn new<T>(value: T) -> Self::Pointer<T>;
+
+    /// Moves the value out if this is the only pointer to it.
+    fn try_unwrap<T>(pointer: Self::Pointer<T>) -> Result<T, Self::Pointer<T>>;
+
+    /// Drops the pointer, moving the value out if it was the last one. Unlike try_unwrap,
+    /// exactly one of several pointers dropped at once from different threads gets the value.
+    fn into_inner<T>(pointer: Self::Pointer<T>) -> Option<T>;
+
+    fn ptr_eq<T>(pointer: &Self::Pointer<T>, other: &Self::Pointer<T>) -> bool;
+
+    /// Returns a mutable reference to the value if this is the only pointer to it.
+    fn get_mut<T>(pointer: &mut Self::Pointer<T>) -> Option<&mut T>;
+
+    fn strong_count<T>(pointer: &Self::Pointer<T>) -> usize;
+
+    /// Returns a mutable reference to the value, cloning it into a new pointer first if
+    /// there are other pointers to it.
+    fn make_mut<T: Clone>(pointer: &mut Self::Pointer<T>) -> &mut T;
+}
+
+/// Shares nodes with Rc. Lists using it can't be sent to other threads.
+pub struct RcK;
+
+/// Shares nodes with Arc.
+pub struct ArcK;
+
+impl SharedPointerKind for RcK {
+    type Pointer<T> = Rc<T>;
+
+    fn new<T>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+
+    fn try_unwrap<T>(pointer: Rc<T>) -> Result<T, Rc<T>> {
+        Rc::try_unwrap(pointer)
+    }
+
+    fn into_inner<T>(pointer: Rc<T>) -> Option<T> {
+        Rc::into_inner(pointer)
+    }
+
+    fn ptr_eq<T>(pointer: &Rc<T>, other: &Rc<T>) -> bool {
+        Rc::ptr_eq(pointer, other)
+    }
+
+    fn get_mut<T>(pointer: &mut Rc<T>) -> Option<&mut T> {
+        Rc::get_mut(pointer)
+    }
+
+    fn strong_count<T>(pointer: &Rc<T>) -> usize {
+        Rc::strong_count(pointer)
+    }
+
+    fn make_mut<T: Clone>(pointer: &mut Rc<T>) -> &mut T {
+        Rc::make_mut(pointer)
+    }
+}
+
+impl SharedPointerKind for ArcK {
+    type Pointer<T> = Arc<T>;
+
+    fn new<T>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+
+    fn try_unwrap<T>(pointer: Arc<T>) -> Result<T, Arc<T>> {
+        Arc::try_unwrap(pointer)
+    }
+
+    fn into_inner<T>(pointer: Arc<T>) -> Option<T> {
+        Arc::into_inner(pointer)
+    }
+
+    fn ptr_eq<T>(pointer: &Arc<T>, other: &Arc<T>) -> bool {
+        Arc::ptr_eq(pointer, other)
+    }
+
+    fn get_mut<T>(pointer: &mut Arc<T>) -> Option<&mut T> {
+        Arc::get_mut(pointer)
+    }
+
+    fn strong_count<T>(pointer: &Arc<T>) -> usize {
+        Arc::strong_count(pointer)
+    }
+
+    fn make_mut<T: Clone>(pointer: &mut Arc<T>) -> &mut T {
+        Arc::make_mut(pointer)
+    }
 }
 
-type Link<T> = Option<Arc<Node<T>>>;
+pub struct List<T, P: SharedPointerKind = ArcK> {
+    head: Link<T, P>
+}
+
+pub type RcList<T> = List<T, RcK>;
+pub type ArcList<T> = List<T, ArcK>;
+
+type Link<T, P> = Option<<P as SharedPointerKind>::Pointer<Node<T, P>>>;
 
-struct Node<T> {
+struct Node<T, P: SharedPointerKind> {
     elem: T,
-    next: Link<T>,
+    // The length of the list starting at this node. Nodes never change once created,
+    // so every suffix knows its own length without walking it.
+    len: usize,
+    next: Link<T, P>,
+}
+
+/// Copies a node so that it can be changed without affecting other lists. The copy shares
+/// the rest of the list with the original.
+impl<T: Clone, P: SharedPointerKind> Clone for Node<T, P> {
+    fn clone(&self) -> Self {
+        Node { elem: self.elem.clone(), len: self.len, next: self.next.clone() }
+    }
+}
+
+impl<T> List<T> {
+    /// Creates an empty list using the default ArcK. Like HashMap::new, this is only defined
+    /// for the default so that `List::new()` doesn't need a type annotation; use
+    /// new_with_ptr_kind or default for other kinds.
+    pub fn new() -> Self {
+        List { head: None }
+    }
 }
 
 /// A persistent linked list
 /// Can't be modified, instead we get a new list which
 /// references or copies from the original.
-impl<T> List<T> {
-    pub fn new() -> Self {
+impl<T, P: SharedPointerKind> List<T, P> {
+    pub fn new_with_ptr_kind() -> Self {
         List { head: None }
     }
 
     /// Replaces the head node of the list with a new node containing the
     /// given element and pointing to the old head.
-    pub fn prepend(&self, elem: T) -> List<T> {
-        List { head: Some(Arc::new(Node {
+    pub fn prepend(&self, elem: T) -> List<T, P> {
+        List { head: Some(P::new(Node {
             elem,
+            len: self.len() + 1,
             next: self.head.clone()
         }))}
     }
 
     /// Returns the node linked to by the head node.
-    pub fn tail(&self) -> List<T> {
+    pub fn tail(&self) -> List<T, P> {
         List { head: self.head.as_ref().and_then(|node| node.next.clone())}
     }
 
@@ -39,29 +174,266 @@ impl<T> List<T> {
         self.head.as_ref().map(|node| &node.elem)
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, P> {
         Iter { next: self.head.as_deref() }
     }
+
+    /// Returns the list without its first n elements, which shares all of its nodes with
+    /// this list. Takes O(n) to walk to the nth node.
+    pub fn drop(&self, n: usize) -> List<T, P> {
+        let mut link = self.head.as_ref();
+        for _ in 0..n {
+            link = link.and_then(|node| node.next.as_ref());
+        }
+        List { head: link.cloned() }
+    }
+
+    /// Returns a new list with `f` applied to every element, in the same order.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> List<U, P> {
+        self.iter().map(f).collect()
+    }
+
+    /// Adds an element to the front of this list in place, rather than returning a new list.
+    pub fn push_front_mut(&mut self, elem: T) {
+        let len = self.len() + 1;
+        let next = self.head.take();
+        self.head = Some(P::new(Node { elem, len, next }));
+    }
+
+    /// Whether both lists start at the same node, and so are the same version. Unlike ==,
+    /// this doesn't look at any elements.
+    pub fn ptr_eq(&self, other: &List<T, P>) -> bool {
+        match (&self.head, &other.head) {
+            (Some(node), Some(other_node)) => P::ptr_eq(node, other_node),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Copy-on-write versions of the operations, like Arc::make_mut. Nodes which only this list
+/// points to are changed in place, and any which are shared with another list are copied
+/// first so that the other list doesn't change. Copying a node shares the node after it
+/// again, so changing an element deep in a shared suffix copies every node before it.
+impl<T: Clone, P: SharedPointerKind> List<T, P> {
+    pub fn head_mut(&mut self) -> Option<&mut T> {
+        self.head.as_mut().map(|node| &mut P::make_mut(node).elem)
+    }
+
+    /// Removes the element at the front of this list in place. The element is moved out
+    /// if no other list shares the head node, and cloned otherwise.
+    pub fn pop_front_mut(&mut self) -> Option<T> {
+        self.head.take().map(|node| match P::try_unwrap(node) {
+            Ok(mut node) => {
+                self.head = node.next.take();
+                node.elem
+            }
+            Err(node) => {
+                self.head = node.next.clone();
+                node.elem.clone()
+            }
+        })
+    }
+
+    /// Iterates over mutable references to the elements, copying each shared node as it's
+    /// reached. Nodes which aren't reached are left shared.
+    pub fn iter_mut_unique(&mut self) -> IterMut<'_, T, P> {
+        IterMut { next: self.head.as_mut() }
+    }
+}
+
+/// Transformations which have to copy elements into new nodes, since a node's successor
+/// can't change. Only concat and split_at share nodes with an existing list, for the part of
+/// their result which ends it; the others copy every node of their result. None of them
+/// recurse, so they work on lists of any length.
+impl<T: Clone, P: SharedPointerKind> List<T, P> {
+    pub fn reverse(&self) -> List<T, P> {
+        let mut reversed = List::new_with_ptr_kind();
+        for elem in self {
+            reversed.push_front_mut(elem.clone());
+        }
+        reversed
+    }
+
+    /// Returns a new list of the elements for which `f` returns true, in the same order.
+    pub fn filter<F: FnMut(&T) -> bool>(&self, mut f: F) -> List<T, P> {
+        self.iter().filter(|elem| f(elem)).cloned().collect()
+    }
+
+    /// Returns this list's elements followed by `other`'s. This list's elements are copied,
+    /// but `other` is shared as the suffix, so this takes O(self.len()) however long `other` is.
+    pub fn concat(&self, other: &List<T, P>) -> List<T, P> {
+        let mut builder = ListBuilder::new_with_ptr_kind();
+        builder.extend(self.iter().cloned());
+        builder.build_onto(other.clone())
+    }
+
+    /// Returns a new list of the first n elements, or all of them if there are fewer.
+    pub fn take(&self, n: usize) -> List<T, P> {
+        self.iter().take(n).cloned().collect()
+    }
+
+    /// Splits the list before the nth element. The first half is copied and the second half
+    /// is shared with this list, just like take and drop, but it only walks the list once.
+    pub fn split_at(&self, n: usize) -> (List<T, P>, List<T, P>) {
+        let mut builder = ListBuilder::new_with_ptr_kind();
+        let mut link = self.head.as_ref();
+        while let Some(node) = link.filter(|_| builder.len() < n) {
+            builder.push_back(node.elem.clone());
+            link = node.next.as_ref();
+        }
+        (builder.build(), List { head: link.cloned() })
+    }
+
+    /// Returns a new list of pairs of elements from both lists, as long as the shorter one.
+    pub fn zip<U: Clone>(&self, other: &List<U, P>) -> List<(T, U), P> {
+        self.iter().cloned().zip(other.iter().cloned()).collect()
+    }
+}
+
+// Makes the compiler check that lists and the types built on them are Send and Sync.
+#[allow(dead_code)]
+fn assert_send_sync<T: Send + Sync>() {
+    fn is_send_sync<L: Send + Sync>() {}
+    is_send_sync::<List<T>>();
+    is_send_sync::<Iter<'_, T>>();
+    is_send_sync::<queue::Queue<T>>();
+    is_send_sync::<atomic::AtomicList<T>>();
+    is_send_sync::<random_access::RandomAccessList<T>>();
+}
+
+impl<T, P: SharedPointerKind> Default for List<T, P> {
+    fn default() -> Self {
+        Self::new_with_ptr_kind()
+    }
+}
+
+/// Pushes and pops in place. Other versions may share the head node, in which case popping
+/// can't move its element out and returns a clone of it instead.
+impl<T: Clone, P: SharedPointerKind> crate::Stack<T> for List<T, P> {
+    fn push(&mut self, elem: T) {
+        self.push_front_mut(elem);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front_mut()
+    }
+}
+
+/// Cloning a list just shares its head node, so it takes O(1) no matter how long the list is.
+impl<T, P: SharedPointerKind> Clone for List<T, P> {
+    fn clone(&self) -> Self {
+        List { head: self.head.clone() }
+    }
+}
+
+impl<T: fmt::Debug, P: SharedPointerKind> fmt::Debug for List<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+/// Compares elements from the head onwards, but stops as soon as both lists reach the same
+/// node: from there on they share the same suffix, so the rest must be equal. This means that
+/// comparing versions which share most of their nodes is cheap. It also means that shared
+/// elements are always treated as equal to themselves, even for a type like f64 where NaN
+/// doesn't equal itself.
+impl<T: PartialEq, P: SharedPointerKind> PartialEq for List<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let mut nodes = (self.head.as_deref(), other.head.as_deref());
+        loop {
+            match nodes {
+                (Some(node), Some(other_node)) => {
+                    if std::ptr::eq(node, other_node) {
+                        return true;
+                    }
+                    if node.elem != other_node.elem {
+                        return false;
+                    }
+                    nodes = (node.next.as_deref(), other_node.next.as_deref());
+                }
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl<T: Eq, P: SharedPointerKind> Eq for List<T, P> {}
+
+/// Lists are ordered lexicographically, comparing elements from the head onwards.
+impl<T: PartialOrd, P: SharedPointerKind> PartialOrd for List<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord, P: SharedPointerKind> Ord for List<T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash, P: SharedPointerKind> Hash for List<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The length is cached in the head node, so this is free, and it marks where this
+        // list's elements end when it's hashed alongside others, e.g. as part of a tuple
+        state.write_usize(self.len());
+        for elem in self {
+            elem.hash(state);
+        }
+    }
 }
 
-impl<T> Drop for List<T> {
+/// Builds the list front to back with a ListBuilder, so the first element yielded ends up
+/// at the head without the elements having to be reversed.
+impl<T, P: SharedPointerKind> FromIterator<T> for List<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut builder = ListBuilder::default();
+        builder.extend(iter);
+        builder.build()
+    }
+}
+
+/// Frees nodes one at a time until it reaches one which another version still uses.
+/// Versions can be dropped from several threads at once, so each node is taken with
+/// into_inner, which hands it to exactly one of them to carry on freeing the rest of the
+/// list iteratively. With try_unwrap they could all fail, leaving the last reference to
+/// drop the rest of the list recursively.
+impl<T, P: SharedPointerKind> Drop for List<T, P> {
     fn drop(&mut self) {
         let mut head = self.head.take();
-        while let Some(node) = head {
-            if let Ok(mut node) = Arc::try_unwrap(node) {
-                head = node.next.take();
-            } else {
-                break;
-            }
+        while let Some(mut node) = head.and_then(P::into_inner) {
+            head = node.next.take();
         }
     }
 }
 
-pub struct Iter<'a, T> {
-    next: Option<&'a Node<T>>
+pub struct Iter<'a, T, P: SharedPointerKind = ArcK> {
+    next: Option<&'a Node<T, P>>
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<'a, T, P: SharedPointerKind> IntoIterator for &'a List<T, P> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, P>;
+
+    fn into_iter(self) -> Iter<'a, T, P> {
+        self.iter()
+    }
+}
+
+impl<'a, T, P: SharedPointerKind> Iterator for Iter<'a, T, P> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -70,11 +442,57 @@ impl<'a, T> Iterator for Iter<'a, T> {
             &node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, |node| node.len);
+        (len, Some(len))
+    }
+}
+
+impl<T, P: SharedPointerKind> ExactSizeIterator for Iter<'_, T, P> {}
+
+impl<T, P: SharedPointerKind> FusedIterator for Iter<'_, T, P> {}
+
+pub struct IterMut<'a, T: 'a, P: SharedPointerKind + 'a = ArcK> {
+    next: Option<&'a mut P::Pointer<Node<T, P>>>,
+}
+
+impl<'a, T: Clone + 'a, P: SharedPointerKind + 'a> Iterator for IterMut<'a, T, P> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            let node = P::make_mut(node);
+            self.next = node.next.as_mut();
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.as_ref().map_or(0, |node| node.len);
+        (len, Some(len))
+    }
 }
 
+impl<T: Clone, P: SharedPointerKind> ExactSizeIterator for IterMut<'_, T, P> {}
+
+impl<T: Clone, P: SharedPointerKind> FusedIterator for IterMut<'_, T, P> {}
+
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{List, RcList};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::hash::{Hash, Hasher};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
 
     #[test]
     fn basics() {
@@ -105,4 +523,230 @@ mod test {
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&1));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn clone_shares_nodes() {
+        let list: List<i32> = (1..=3).collect();
+        let copy = list.clone();
+        assert!(std::ptr::eq(list.head().unwrap(), copy.head().unwrap()));
+        drop(list);
+        assert_eq!(copy.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn eq() {
+        let base: List<i32> = (2..=4).collect();
+        let one = base.prepend(1);
+        let other_one = base.prepend(1);
+        let fresh: List<i32> = (1..=4).collect();
+        assert_eq!(one, other_one);
+        assert_eq!(one, fresh);
+        assert_ne!(one, base);
+        assert_ne!(one, base.prepend(0));
+        assert_eq!(List::<i32>::new(), List::new());
+
+        // The shared suffix is equal to itself without comparing its elements
+        let nan = List::new().prepend(f64::NAN);
+        assert_eq!(nan.prepend(1.0), nan.prepend(1.0));
+        assert_ne!(nan, List::new().prepend(f64::NAN));
+    }
+
+    #[test]
+    fn ord_hash_debug() {
+        let list: List<i32> = (1..=3).collect();
+        let shorter = list.tail();
+        assert!(list < shorter);
+        assert!(list.tail().tail() > shorter);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(hash_of(&list), hash_of(&(1..=3).collect::<List<i32>>()));
+
+        let mut versions = HashMap::new();
+        versions.insert(list.clone(), "three");
+        versions.insert(list.tail(), "two");
+        assert_eq!(versions.get(&list.tail().prepend(1)), Some(&"three"));
+        assert_eq!(versions.get(&shorter), Some(&"two"));
+        assert_eq!(versions.get(&List::default()), None);
+    }
+
+    /// Threads drop clones of the same long list at the same time, so whichever drops it last
+    /// has to free the whole chain without recursing. The threads spin until they're all
+    /// ready so that their drops line up as closely as possible. Once they've finished, the
+    /// token which every element cloned should be back to the test's one reference.
+    #[test]
+    fn concurrent_drops() {
+        const THREADS: usize = 8;
+        const ROUNDS: usize = 4;
+        let token = Arc::new(());
+        for _ in 0..ROUNDS {
+            let base: List<Arc<()>> = (0..1_000_000).map(|_| token.clone()).collect();
+            let waiting = Arc::new(AtomicUsize::new(THREADS));
+            let handles: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    let version = base.clone();
+                    let waiting = waiting.clone();
+                    thread::spawn(move || {
+                        waiting.fetch_sub(1, Ordering::SeqCst);
+                        while waiting.load(Ordering::SeqCst) > 0 {
+                            std::hint::spin_loop();
+                        }
+                        drop(version);
+                    })
+                })
+                .collect();
+            drop(base);
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        }
+        assert_eq!(Arc::strong_count(&token), 1);
+    }
+
+    #[test]
+    fn head_mut() {
+        let mut list: List<i32> = (1..=3).collect();
+        let head = list.head().unwrap() as *const i32;
+        *list.head_mut().unwrap() = 10;
+        // Nothing else shares the head, so it's changed in place
+        assert_eq!(list.head().unwrap() as *const i32, head);
+
+        let snapshot = list.clone();
+        *list.head_mut().unwrap() = 100;
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![100, 2, 3]);
+        assert_eq!(snapshot.iter().copied().collect::<Vec<_>>(), vec![10, 2, 3]);
+        assert!(std::ptr::eq(list.tail().head().unwrap(), snapshot.tail().head().unwrap()));
+        assert_eq!(List::<i32>::new().head_mut(), None);
+    }
+
+    #[test]
+    fn push_and_pop_front_mut() {
+        let token = Arc::new(());
+        let mut list = List::new();
+        list.push_front_mut(token.clone());
+        list.push_front_mut(token.clone());
+        assert_eq!(list.len(), 2);
+        let snapshot = list.tail();
+
+        // The head is only in this list, so its element is moved out rather than cloned
+        drop(list.pop_front_mut());
+        assert_eq!(Arc::strong_count(&token), 2);
+        // The next node is shared with the snapshot, so its element is cloned
+        let popped = list.pop_front_mut();
+        assert_eq!(Arc::strong_count(&token), 3);
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front_mut(), None);
+        assert_eq!(snapshot.len(), 1);
+        drop((popped, snapshot));
+        assert_eq!(Arc::strong_count(&token), 1);
+    }
+
+    #[test]
+    fn iter_mut_unique() {
+        let shared: List<i32> = (3..=5).collect();
+        let mut list = shared.prepend(2).prepend(1);
+        let second = list.tail().head().unwrap() as *const i32;
+
+        let mut iter = list.iter_mut_unique();
+        assert_eq!(iter.len(), 5);
+        *iter.next().unwrap() *= 10;
+        *iter.next().unwrap() *= 10;
+        *iter.next().unwrap() *= 10;
+        assert_eq!(iter.len(), 2);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![10, 20, 30, 4, 5]);
+        assert_eq!(shared.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+        // Unshared nodes are changed in place, and nodes past the last one reached stay shared
+        assert_eq!(list.tail().head().unwrap() as *const i32, second);
+        assert!(std::ptr::eq(list.tail().tail().tail().head().unwrap(), shared.tail().head().unwrap()));
+
+        list.iter_mut_unique().for_each(|elem| *elem += 1);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![11, 21, 31, 5, 6]);
+        assert_eq!(shared.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn transformations() {
+        fn collect<T: Clone>(list: &List<T>) -> Vec<T> {
+            list.iter().cloned().collect()
+        }
+
+        let list: List<i32> = (1..=5).collect();
+        let empty = List::<i32>::new();
+        assert_eq!(collect(&list.reverse()), vec![5, 4, 3, 2, 1]);
+        assert_eq!(collect(&list.map(|elem| elem * 10)), vec![10, 20, 30, 40, 50]);
+        assert_eq!(collect(&list.filter(|elem| elem % 2 == 1)), vec![1, 3, 5]);
+        assert_eq!(collect(&list.take(2)), vec![1, 2]);
+        assert_eq!(collect(&list.take(10)), vec![1, 2, 3, 4, 5]);
+        assert_eq!(collect(&list.drop(3)), vec![4, 5]);
+        assert!(list.drop(10).is_empty());
+        assert_eq!(collect(&list.zip(&list.drop(2).map(|elem| elem.to_string()))), vec![
+            (1, "3".to_string()),
+            (2, "4".to_string()),
+            (3, "5".to_string()),
+        ]);
+        assert!(empty.reverse().is_empty());
+        assert_eq!(empty.concat(&list), list);
+
+        // Dropping and concatenating share the suffix rather than copying it
+        let suffix = list.drop(3);
+        assert!(std::ptr::eq(suffix.head().unwrap(), list.tail().tail().tail().head().unwrap()));
+        let joined = list.take(2).concat(&suffix);
+        assert_eq!(collect(&joined), vec![1, 2, 4, 5]);
+        assert_eq!(joined.len(), 4);
+        assert!(std::ptr::eq(joined.drop(2).head().unwrap(), suffix.head().unwrap()));
+
+        let (front, back) = list.split_at(2);
+        assert_eq!((collect(&front), collect(&back)), (vec![1, 2], vec![3, 4, 5]));
+        assert!(std::ptr::eq(back.head().unwrap(), list.drop(2).head().unwrap()));
+        let (front, back) = list.split_at(7);
+        assert_eq!((front, back.is_empty()), (list.clone(), true));
+    }
+
+    #[test]
+    fn long_transformations() {
+        let list: List<u32> = (0..1_000_000).collect();
+        let reversed = list.reverse();
+        assert_eq!(reversed.head(), Some(&999_999));
+        let doubled = list.map(|elem| elem * 2).filter(|elem| elem % 4 == 0);
+        assert_eq!(doubled.len(), 500_000);
+        let (front, back) = list.split_at(500_000);
+        assert_eq!(front.concat(&back), list);
+        assert_eq!(list.zip(&reversed).drop(999_999).head(), Some(&(999_999, 0)));
+        assert_eq!(list.take(999_999).len(), 999_999);
+    }
+
+    #[test]
+    fn rc_list() {
+        let empty = RcList::new_with_ptr_kind();
+        let list = empty.prepend(3).prepend(2).prepend(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.tail().iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(list.clone(), (1..=3).collect::<RcList<i32>>());
+        assert!(std::ptr::eq(list.tail().head().unwrap(), list.clone().tail().head().unwrap()));
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert!(RcList::<i32>::default().is_empty());
+        drop(list);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn len() {
+        let empty = List::new();
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+
+        let list = empty.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+        assert_eq!(list.tail().len(), 2);
+        assert_eq!(list.tail().prepend(4).prepend(5).len(), 4);
+        assert_eq!(empty.len(), 0);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        iter.by_ref().for_each(drop);
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+    }
+}
@@ -0,0 +1,146 @@
+use super::List;
+use std::sync::{Mutex, MutexGuard};
+
+/// A shared cell holding the current version of a list, which threads can replace with a
+/// compare-and-swap. A thread reads the current version, builds a new version from it, then
+/// swaps the new version in only if nobody else has swapped in one of their own meanwhile.
+/// Versions are compared by their head node, so the check is O(1) whatever the lists contain.
+///
+/// Despite the name this isn't lock-free: the current version is kept behind a Mutex, and
+/// every operation locks it. The lock is only held for as long as it takes to clone, compare
+/// or replace the head pointer. New versions are built and old ones dropped outside the lock,
+/// so slow updates and dropping long lists don't hold up other threads.
+pub struct AtomicList<T> {
+    current: Mutex<List<T>>,
+}
+
+impl<T> AtomicList<T> {
+    pub fn new(list: List<T>) -> Self {
+        AtomicList { current: Mutex::new(list) }
+    }
+
+    /// Returns the current version.
+    pub fn load(&self) -> List<T> {
+        self.lock().clone()
+    }
+
+    /// Replaces the current version, returning the old one.
+    pub fn swap(&self, list: List<T>) -> List<T> {
+        std::mem::replace(&mut *self.lock(), list)
+    }
+
+    pub fn store(&self, list: List<T>) {
+        drop(self.swap(list));
+    }
+
+    /// Replaces the current version with `new` if it's still `current`, returning the version
+    /// it replaced. Otherwise gives back `new`.
+    pub fn compare_and_swap(&self, current: &List<T>, new: List<T>) -> Result<List<T>, List<T>> {
+        let mut guard = self.lock();
+        if !guard.ptr_eq(current) {
+            return Err(new);
+        }
+        Ok(std::mem::replace(&mut *guard, new))
+    }
+
+    /// Builds a new version from the current one and swaps it in, retrying with the latest
+    /// version if another thread swaps first. Returns the new version.
+    /// `f` may be called several times, so it shouldn't have side effects.
+    pub fn update<F: FnMut(&List<T>) -> List<T>>(&self, mut f: F) -> List<T> {
+        let mut current = self.load();
+        loop {
+            let new = f(&current);
+            match self.compare_and_swap(&current, new.clone()) {
+                Ok(_) => return new,
+                Err(_) => current = self.load(),
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> List<T> {
+        self.current.into_inner().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, List<T>> {
+        // Nothing can panic while the lock is held, so it can't be poisoned
+        self.current.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<T> Default for AtomicList<T> {
+    fn default() -> Self {
+        Self::new(List::new())
+    }
+}
+
+impl<T> From<List<T>> for AtomicList<T> {
+    fn from(list: List<T>) -> Self {
+        Self::new(list)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AtomicList;
+    use crate::third::List;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let cell = AtomicList::default();
+        let empty = cell.load();
+        assert!(empty.is_empty());
+
+        let one = cell.update(|list| list.prepend(1));
+        assert_eq!(cell.load(), one);
+
+        // A stale version doesn't match, even though its elements are equal
+        assert!(cell.compare_and_swap(&empty, empty.prepend(2)).is_err());
+        assert!(cell.compare_and_swap(&List::new().prepend(1), one.prepend(2)).is_err());
+        let old = cell.compare_and_swap(&one, one.prepend(2)).unwrap();
+        assert_eq!(old, one);
+
+        assert_eq!(cell.swap(List::new()).iter().copied().collect::<Vec<_>>(), vec![2, 1]);
+        cell.store(empty.prepend(3));
+        assert_eq!(cell.into_inner().head(), Some(&3));
+    }
+
+    /// Every thread prepends its own elements, so each one should end up in the final
+    /// version exactly once. Each element carries a clone of a token, so the token's count
+    /// must match the final list's length, and drop back to one along with the list.
+    #[test]
+    fn concurrent_updates() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 2_000;
+        let token = Arc::new(());
+        let cell = Arc::new(AtomicList::default());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let cell = cell.clone();
+                let token = token.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_THREAD {
+                        let elem = thread * PER_THREAD + i;
+                        let version = cell.update(|list| list.prepend((elem, token.clone())));
+                        assert_eq!(version.head().map(|(elem, _)| *elem), Some(elem));
+                        // Dropping old versions here races with the other threads' drops
+                        drop(version.tail());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let list = Arc::try_unwrap(cell).ok().unwrap().into_inner();
+        assert_eq!(list.len(), THREADS * PER_THREAD);
+        let mut elems: Vec<_> = list.iter().map(|(elem, _)| *elem).collect();
+        elems.sort_unstable();
+        assert_eq!(elems, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+        assert_eq!(Arc::strong_count(&token), THREADS * PER_THREAD + 1);
+        drop(list);
+        assert_eq!(Arc::strong_count(&token), 1);
+    }
+}
@@ -0,0 +1,162 @@
+use super::{ArcK, Link, List, Node, SharedPointerKind};
+use std::ptr::NonNull;
+
+/// Builds a list from front to back, for when the elements come in the order they should
+/// end up in. Lists themselves can only grow at the front, since their nodes may be shared
+/// and never change. The builder's nodes aren't shared with anything yet, so it can keep
+/// hold of the last node and link new nodes onto it, appending each element in O(1).
+///
+/// Each node caches the length of the list from there onwards, which isn't known until the
+/// last element has been pushed, so build walks the list once to fill them in.
+pub struct ListBuilder<T, P: SharedPointerKind = ArcK> {
+    head: Link<T, P>,
+    // The last node. Every node is only pointed to by the one before it (or head), so
+    // nothing else can be looking at it.
+    tail: Option<NonNull<Node<T, P>>>,
+    len: usize,
+}
+
+impl<T> ListBuilder<T> {
+    /// Like List::new, this is only defined for the default ArcK.
+    pub fn new() -> Self {
+        Self::new_with_ptr_kind()
+    }
+}
+
+impl<T, P: SharedPointerKind> ListBuilder<T, P> {
+    pub fn new_with_ptr_kind() -> Self {
+        ListBuilder { head: None, tail: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds an element after the ones pushed so far.
+    pub fn push_back(&mut self, elem: T) {
+        let mut node = P::new(Node { elem, len: 0, next: None });
+        let new_tail = NonNull::from(P::get_mut(&mut node).expect("a new node isn't shared"));
+        match self.tail {
+            // SAFETY: the tail is a node in this builder's list which nothing else points to,
+            // and moving its pointer around as the list grew didn't move the node itself.
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+        self.tail = Some(new_tail);
+        self.len += 1;
+    }
+
+    /// Freezes the elements into a list, with the first one pushed at its head.
+    pub fn build(self) -> List<T, P> {
+        self.build_onto(List::new_with_ptr_kind())
+    }
+
+    /// Like build, but the last element is followed by the elements of `suffix`, whose nodes
+    /// are shared rather than copied.
+    pub fn build_onto(mut self, mut suffix: List<T, P>) -> List<T, P> {
+        let mut len = self.len + suffix.len();
+        let suffix = suffix.head.take();
+        match self.tail {
+            // SAFETY: as in push_back.
+            Some(tail) => unsafe { (*tail.as_ptr()).next = suffix },
+            None => self.head = suffix,
+        }
+        // Only the builder's own nodes need their lengths filling in, and only they're unique
+        let mut link = self.head.as_mut();
+        for _ in 0..self.len {
+            let node = P::get_mut(link.expect("the builder has len nodes"))
+                .expect("nodes in a builder aren't shared");
+            node.len = len;
+            len -= 1;
+            link = node.next.as_mut();
+        }
+        List { head: self.head.take() }
+    }
+}
+
+impl<T, P: SharedPointerKind> Default for ListBuilder<T, P> {
+    fn default() -> Self {
+        Self::new_with_ptr_kind()
+    }
+}
+
+impl<T, P: SharedPointerKind> Extend<T> for ListBuilder<T, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+/// Hands the nodes to a list to drop, which does so without recursing.
+impl<T, P: SharedPointerKind> Drop for ListBuilder<T, P> {
+    fn drop(&mut self) {
+        drop(List::<T, P> { head: self.head.take() });
+    }
+}
+
+// SAFETY: the tail pointer only points into the builder's own nodes, so the builder can go
+// wherever the list it builds can.
+unsafe impl<T, P: SharedPointerKind> Send for ListBuilder<T, P> where List<T, P>: Send {}
+unsafe impl<T, P: SharedPointerKind> Sync for ListBuilder<T, P> where List<T, P>: Sync {}
+
+#[cfg(test)]
+mod test {
+    use super::ListBuilder;
+    use crate::third::RcList;
+
+    #[test]
+    fn basics() {
+        let mut builder = ListBuilder::new();
+        assert!(builder.is_empty());
+        builder.push_back(1);
+        builder.push_back(2);
+        builder.extend(3..=4);
+        assert_eq!(builder.len(), 4);
+
+        let list = builder.build();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.tail().tail().len(), 2);
+        assert_eq!(list.iter().len(), 4);
+        assert!(ListBuilder::<i32>::new().build().is_empty());
+    }
+
+    #[test]
+    fn build_onto() {
+        let suffix: crate::third::List<i32> = (3..=4).collect();
+        let mut builder = ListBuilder::new();
+        builder.extend(1..=2);
+        let list = builder.build_onto(suffix.clone());
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.tail().len(), 3);
+        assert!(std::ptr::eq(list.tail().tail().head().unwrap(), suffix.head().unwrap()));
+        assert_eq!(ListBuilder::new().build_onto(suffix.clone()), suffix);
+    }
+
+    #[test]
+    fn rc_builder() {
+        let mut builder = ListBuilder::new_with_ptr_kind();
+        builder.push_back('a');
+        builder.push_back('b');
+        let list: RcList<char> = builder.build();
+        assert_eq!(list.prepend('z').iter().collect::<String>(), "zab");
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn long_list() {
+        let list: crate::third::List<u32> = (0..1_000_000).collect();
+        assert_eq!(list.len(), 1_000_000);
+        assert_eq!(list.head(), Some(&0));
+
+        // Dropping a builder without building frees its nodes without recursing
+        let mut builder = ListBuilder::new();
+        builder.extend(0..1_000_000);
+    }
+}
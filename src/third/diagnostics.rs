@@ -0,0 +1,125 @@
+use super::{List, Node, SharedPointerKind};
+use std::collections::HashSet;
+use std::mem;
+
+/// Ways to see how much of their structure lists share, for deciding when keeping old
+/// versions around has become too expensive.
+impl<T, P: SharedPointerKind> List<T, P> {
+    /// Returns how many nodes at the end of this list are the very same nodes as at the end
+    /// of `other`. Lists which merely have equal elements share nothing.
+    pub fn shared_suffix_len(&self, other: &List<T, P>) -> usize {
+        // A shared suffix is the same length in both lists, so line the lists up by length
+        let (mut list, mut other) = if self.len() > other.len() {
+            (self.drop(self.len() - other.len()), other.clone())
+        } else {
+            (self.clone(), other.drop(other.len() - self.len()))
+        };
+        while !list.ptr_eq(&other) {
+            list = list.tail();
+            other = other.tail();
+        }
+        list.len()
+    }
+
+    /// Returns how many nodes at the front of this list aren't shared with any other list,
+    /// and so would be freed if it was dropped. Everything after the first shared node is
+    /// kept alive by whatever shares it.
+    pub fn unique_prefix_len(&self) -> usize {
+        let mut len = 0;
+        let mut link = self.head.as_ref();
+        while let Some(node) = link.filter(|node| P::strong_count(node) == 1) {
+            len += 1;
+            link = node.next.as_ref();
+        }
+        len
+    }
+}
+
+/// A summary of the nodes used by a set of list versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryReport {
+    /// The number of distinct nodes reachable from any of the versions.
+    pub nodes: usize,
+    /// How many of those nodes are reachable from more than one of the versions.
+    pub shared_nodes: usize,
+    /// An estimate of the memory taken by the nodes: each node's size plus the two reference
+    /// counts kept by Rc and Arc. Memory that the elements themselves point to isn't included.
+    pub bytes: usize,
+}
+
+impl MemoryReport {
+    /// Walks every version, counting each node once however many versions contain it.
+    /// A version stops being walked as soon as it reaches a node that's been seen before,
+    /// so this takes time proportional to the number of distinct nodes.
+    pub fn new<'a, T: 'a, P: SharedPointerKind + 'a>(
+        versions: impl IntoIterator<Item = &'a List<T, P>>,
+    ) -> Self {
+        let mut seen: HashSet<*const Node<T, P>> = HashSet::new();
+        let mut shared: HashSet<*const Node<T, P>> = HashSet::new();
+        for version in versions {
+            let mut node = version.head.as_deref();
+            while let Some(current) = node.filter(|current| seen.insert(*current)) {
+                node = current.next.as_deref();
+            }
+            // Everything from the first node seen before is reachable from an earlier version.
+            // Stop at a node which is already marked, since so is the rest of its list.
+            while let Some(current) = node.filter(|current| shared.insert(*current)) {
+                node = current.next.as_deref();
+            }
+        }
+        MemoryReport {
+            nodes: seen.len(),
+            shared_nodes: shared.len(),
+            bytes: seen.len() * (mem::size_of::<Node<T, P>>() + 2 * mem::size_of::<usize>()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryReport;
+    use crate::third::{List, RcList};
+
+    #[test]
+    fn suffixes_and_prefixes() {
+        let base: List<i32> = (3..=5).collect();
+        let one = base.prepend(2).prepend(1);
+        let other = base.tail().prepend(0);
+        assert_eq!(one.shared_suffix_len(&other), 2);
+        assert_eq!(other.shared_suffix_len(&one), 2);
+        assert_eq!(one.shared_suffix_len(&base), 3);
+        assert_eq!(one.shared_suffix_len(&(1..=5).collect()), 0);
+        assert_eq!(one.shared_suffix_len(&List::new()), 0);
+
+        assert!(one.ptr_eq(&one.clone()));
+        assert!(!one.ptr_eq(&base.prepend(2).prepend(1)));
+        assert!(List::<i32>::new().ptr_eq(&one.drop(5)));
+
+        // Two nodes are only in one; base's are shared with it
+        assert_eq!(one.unique_prefix_len(), 2);
+        drop(other);
+        // A clone shares the head node, so nothing is unique to either
+        let copy = one.clone();
+        assert_eq!(one.unique_prefix_len(), 0);
+        drop(copy);
+        drop(base);
+        assert_eq!(one.unique_prefix_len(), 5);
+    }
+
+    #[test]
+    fn memory_report() {
+        let base: RcList<u64> = (3..=5).collect();
+        let one = base.prepend(2).prepend(1);
+        let other = base.tail().prepend(0);
+        let report = MemoryReport::new([&one, &other, &base]);
+        assert_eq!(report.nodes, 6);
+        // base's three nodes are in one, and two of them are in other too
+        assert_eq!(report.shared_nodes, 3);
+        assert_eq!(report.bytes, 6 * MemoryReport::new([&base.drop(2)]).bytes);
+
+        let twice = MemoryReport { shared_nodes: 5, ..MemoryReport::new([&one]) };
+        assert_eq!(MemoryReport::new([&one, &one]), twice);
+        assert_eq!(MemoryReport::new([&one.drop(2)]).shared_nodes, 0);
+        assert_eq!(MemoryReport::new(Vec::<&RcList<u64>>::new()), MemoryReport::default());
+    }
+}
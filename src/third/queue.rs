@@ -0,0 +1,177 @@
+use super::List;
+use std::iter::FusedIterator;
+
+/// A persistent FIFO queue made from two persistent lists (Okasaki's batched queue).
+/// Elements are taken off the front list and added onto the back list, which is kept in
+/// reverse order. When the front list runs out, the back list is reversed to become the new
+/// front. Every operation returns a new queue, and old versions stay valid and share their
+/// lists with the new ones.
+///
+/// Reversing the back list is O(n), but each element is only reversed once as a queue is
+/// used, so snoc and tail are amortised O(1). Reversing copies elements into new nodes,
+/// which is why they need to be Clone. The amortised bound only holds if each version is
+/// built on at most once: calling tail repeatedly on the same version that needs a reversal
+/// repeats the reversal every time.
+pub struct Queue<T> {
+    // Invariant: front is only empty if the whole queue is empty.
+    front: List<T>,
+    back: List<T>,
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Queue { front: List::new(), back: List::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty()
+    }
+
+    /// Returns a reference to the element at the front of the queue.
+    pub fn head(&self) -> Option<&T> {
+        self.front.head()
+    }
+
+    /// Iterates from the front of the queue to the back. The back list has to be walked
+    /// in reverse, so its elements are gathered up front when the iterator is created.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut back: Vec<&T> = self.back.iter().collect();
+        back.reverse();
+        Iter { front: self.front.iter(), back: back.into_iter() }
+    }
+}
+
+impl<T: Clone> Queue<T> {
+    /// Returns a new queue with the element added onto the back.
+    pub fn snoc(&self, elem: T) -> Queue<T> {
+        Self::check(self.front.clone(), self.back.prepend(elem))
+    }
+
+    /// Returns a new queue without the element at the front.
+    pub fn tail(&self) -> Queue<T> {
+        Self::check(self.front.tail(), self.back.clone())
+    }
+
+    /// Restores the invariant by reversing the back list onto the front if the front is empty.
+    fn check(front: List<T>, back: List<T>) -> Queue<T> {
+        if !front.is_empty() {
+            Queue { front, back }
+        } else {
+            let front = back.iter().fold(List::new(), |front, elem| front.prepend(elem.clone()));
+            Queue { front, back: List::new() }
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Replaces the queue with a new version on each enqueue or dequeue. Like the Stack
+/// implementation for List, dequeuing returns a clone of the element at the front.
+impl<T: Clone> crate::Queue<T> for Queue<T> {
+    fn enqueue(&mut self, elem: T) {
+        *self = self.snoc(elem);
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        let elem = self.head().cloned();
+        *self = self.tail();
+        elem
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: super::Iter<'a, T>,
+    back: std::vec::IntoIter<&'a T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::Queue;
+
+    fn collect(queue: &Queue<i32>) -> Vec<i32> {
+        queue.iter().copied().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.head(), None);
+        assert!(queue.tail().is_empty());
+
+        let queue = queue.snoc(1).snoc(2).snoc(3);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.tail().len(), 2);
+        assert_eq!(queue.head(), Some(&1));
+
+        let queue = queue.tail();
+        assert_eq!(queue.head(), Some(&2));
+
+        let queue = queue.snoc(4).tail();
+        assert_eq!(queue.head(), Some(&3));
+
+        let queue = queue.tail();
+        assert_eq!(queue.head(), Some(&4));
+
+        let queue = queue.tail();
+        assert_eq!(queue.head(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn persistence() {
+        let one = Queue::new().snoc(1);
+        let two = one.snoc(2);
+        let three = two.snoc(3);
+        let other = two.snoc(30);
+        let popped = three.tail();
+
+        assert_eq!(collect(&one), vec![1]);
+        assert_eq!(collect(&two), vec![1, 2]);
+        assert_eq!(collect(&three), vec![1, 2, 3]);
+        assert_eq!(collect(&other), vec![1, 2, 30]);
+        assert_eq!(collect(&popped), vec![2, 3]);
+        assert_eq!(collect(&popped.tail().snoc(4)), vec![3, 4]);
+        assert_eq!(collect(&three), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn iter() {
+        let queue = Queue::new().snoc(1).snoc(2).snoc(3).tail().snoc(4).snoc(5);
+        let mut iter = queue.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), Some(&4));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(&5));
+        assert_eq!(iter.next(), None);
+    }
+}
@@ -0,0 +1,359 @@
+use super::List;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::sync::Arc;
+
+/// A persistent list with O(log n) indexing (Okasaki's skew binary random-access list).
+/// The elements are stored in complete binary trees of sizes 2^k - 1, kept in a persistent
+/// list from smallest to largest. Only the two smallest trees may be the same size, which
+/// means there are at most O(log n) trees, each O(log n) deep.
+///
+/// Adding an element either joins the two smallest trees under a new root, if they're the
+/// same size, or adds it as a new tree of one; taking the first element off splits the first
+/// tree back into its two halves. Both are O(1). Like List, each version shares its trees
+/// with the version it was made from, and nodes are shared through Arcs.
+pub struct RandomAccessList<T> {
+    // Each tree with its size
+    trees: List<(usize, Arc<Tree<T>>)>,
+    len: usize,
+}
+
+/// A complete binary tree, whose elements are numbered root first, then the left subtree,
+/// then the right subtree.
+enum Tree<T> {
+    Leaf(T),
+    Node(T, Arc<Tree<T>>, Arc<Tree<T>>),
+}
+
+impl<T> Tree<T> {
+    fn elem(&self) -> &T {
+        match self {
+            Tree::Leaf(elem) | Tree::Node(elem, _, _) => elem,
+        }
+    }
+}
+
+impl<T> RandomAccessList<T> {
+    pub fn new() -> Self {
+        RandomAccessList { trees: List::new(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a new list with the element added at the front.
+    pub fn cons(&self, elem: T) -> RandomAccessList<T> {
+        let mut trees = self.trees.iter();
+        let trees = match (trees.next(), trees.next()) {
+            (Some((size, left)), Some((other_size, right))) if size == other_size => {
+                let tree = Tree::Node(elem, left.clone(), right.clone());
+                self.trees.drop(2).prepend((2 * size + 1, Arc::new(tree)))
+            }
+            _ => self.trees.prepend((1, Arc::new(Tree::Leaf(elem)))),
+        };
+        RandomAccessList { trees, len: self.len + 1 }
+    }
+
+    /// Returns a reference to the first element in the list.
+    pub fn head(&self) -> Option<&T> {
+        self.trees.head().map(|(_, tree)| tree.elem())
+    }
+
+    /// Returns a new list without the first element.
+    pub fn tail(&self) -> RandomAccessList<T> {
+        let trees = match self.trees.head() {
+            Some((size, tree)) => match &**tree {
+                Tree::Leaf(_) => self.trees.tail(),
+                Tree::Node(_, left, right) => {
+                    let half = size / 2;
+                    self.trees.tail().prepend((half, right.clone())).prepend((half, left.clone()))
+                }
+            },
+            None => return RandomAccessList::new(),
+        };
+        RandomAccessList { trees, len: self.len - 1 }
+    }
+
+    /// Returns a reference to the element at the index, or None if it's out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let (mut size, tree, mut index) = self.find_tree(index)?;
+        let mut tree = &**tree;
+        while index > 0 {
+            let Tree::Node(_, left, right) = tree else {
+                unreachable!("a leaf only has index 0")
+            };
+            size /= 2;
+            if index <= size {
+                tree = left;
+                index -= 1;
+            } else {
+                tree = right;
+                index -= 1 + size;
+            }
+        }
+        Some(tree.elem())
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { trees: self.trees.iter(), stack: Vec::new(), len: self.len }
+    }
+
+    /// Finds the tree containing the index, returning its size and the index within it.
+    fn find_tree(&self, mut index: usize) -> Option<(usize, &Arc<Tree<T>>, usize)> {
+        for (size, tree) in &self.trees {
+            if index < *size {
+                return Some((*size, tree, index));
+            }
+            index -= size;
+        }
+        None
+    }
+}
+
+impl<T: Clone> RandomAccessList<T> {
+    /// Returns a new list with the element at the index replaced, or None if it's out of
+    /// bounds. Only the trees before the one containing the index and the path down to the
+    /// element are copied, so this is O(log n), and everything else is shared.
+    pub fn set(&self, index: usize, elem: T) -> Option<RandomAccessList<T>> {
+        let mut before = Vec::new();
+        let mut rest = self.trees.clone();
+        let mut index = index;
+        let (size, tree) = loop {
+            let (size, tree) = rest.head()?.clone();
+            rest = rest.tail();
+            if index < size {
+                break (size, tree);
+            }
+            index -= size;
+            before.push((size, tree));
+        };
+
+        // Walk down to the element, remembering the way back up
+        let mut path = Vec::new();
+        let mut node = &*tree;
+        let mut subtree_size = size;
+        while index > 0 {
+            let Tree::Node(_, left, right) = node else {
+                unreachable!("a leaf only has index 0")
+            };
+            subtree_size /= 2;
+            let went_left = index <= subtree_size;
+            path.push((node, went_left));
+            if went_left {
+                node = left;
+                index -= 1;
+            } else {
+                node = right;
+                index -= 1 + subtree_size;
+            }
+        }
+        let mut new_tree = Arc::new(match node {
+            Tree::Leaf(_) => Tree::Leaf(elem),
+            Tree::Node(_, left, right) => Tree::Node(elem, left.clone(), right.clone()),
+        });
+        // Copy each node on the path, pointing it at the new copy below it
+        for (node, went_left) in path.into_iter().rev() {
+            let Tree::Node(elem, left, right) = node else {
+                unreachable!("the path only goes through nodes")
+            };
+            new_tree = Arc::new(if went_left {
+                Tree::Node(elem.clone(), new_tree, right.clone())
+            } else {
+                Tree::Node(elem.clone(), left.clone(), new_tree)
+            });
+        }
+
+        let mut trees = rest.prepend((size, new_tree));
+        for tree in before.into_iter().rev() {
+            trees = trees.prepend(tree);
+        }
+        Some(RandomAccessList { trees, len: self.len })
+    }
+}
+
+impl<T> Default for RandomAccessList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cloning shares all of the trees, so it takes O(1).
+impl<T> Clone for RandomAccessList<T> {
+    fn clone(&self) -> Self {
+        RandomAccessList { trees: self.trees.clone(), len: self.len }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RandomAccessList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for RandomAccessList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for RandomAccessList<T> {}
+
+/// The first element yielded becomes the head. Trees can only be built up from the front,
+/// so the elements are gathered into a Vec first and consed on from the last one.
+impl<T> FromIterator<T> for RandomAccessList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+        elems.into_iter().rev().fold(RandomAccessList::new(), |list, elem| list.cons(elem))
+    }
+}
+
+/// Copies the elements into new trees, in the same order.
+impl<T: Clone> From<&List<T>> for RandomAccessList<T> {
+    fn from(list: &List<T>) -> Self {
+        list.iter().cloned().collect()
+    }
+}
+
+/// Copies the elements into a new list, in the same order.
+impl<T: Clone> From<&RandomAccessList<T>> for List<T> {
+    fn from(list: &RandomAccessList<T>) -> Self {
+        list.iter().cloned().collect()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RandomAccessList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Walks each tree in turn, keeping a stack of the subtrees still to visit in the current one.
+pub struct Iter<'a, T> {
+    trees: super::Iter<'a, (usize, Arc<Tree<T>>)>,
+    stack: Vec<&'a Tree<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = match self.stack.pop() {
+            Some(tree) => tree,
+            None => &**self.trees.next().map(|(_, tree)| tree)?,
+        };
+        if let Tree::Node(_, left, right) = tree {
+            self.stack.push(right);
+            self.stack.push(left);
+        }
+        self.len -= 1;
+        Some(tree.elem())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::RandomAccessList;
+    use crate::third::List;
+
+    fn collect(list: &RandomAccessList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let list = RandomAccessList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.head(), None);
+        assert!(list.tail().is_empty());
+
+        let list = list.cons(3).cons(2).cons(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(collect(&list), vec![1, 2, 3]);
+        assert_eq!(list.tail().head(), Some(&2));
+        assert_eq!(collect(&list.tail().tail()), vec![3]);
+        assert!(list.tail().tail().tail().is_empty());
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn get_and_set() {
+        let list: RandomAccessList<i32> = (0..100).collect();
+        for i in 0..100 {
+            assert_eq!(list.get(i as usize), Some(&i));
+        }
+        assert_eq!(list.get(100), None);
+
+        let changed = list.set(42, -1).unwrap();
+        assert_eq!(changed.get(42), Some(&-1));
+        assert_eq!(list.get(42), Some(&42));
+        assert_eq!(changed.len(), 100);
+        let mut expected: Vec<i32> = (0..100).collect();
+        expected[42] = -1;
+        assert_eq!(collect(&changed), expected);
+        assert_eq!(list.set(100, 0), None);
+
+        // Every index can be set, whichever tree and position it's in
+        for i in 0..100 {
+            let changed = list.set(i, 1000).unwrap();
+            assert_eq!(changed.get(i), Some(&1000));
+            assert_eq!(changed.iter().filter(|elem| **elem == 1000).count(), 1);
+        }
+    }
+
+    #[test]
+    fn tails_and_sharing() {
+        let list: RandomAccessList<i32> = (0..20).collect();
+        let mut rest = list.clone();
+        for i in 0..20 {
+            assert_eq!(rest.head(), Some(&i));
+            assert_eq!(rest.get(0), Some(&i));
+            assert_eq!(rest.len(), 20 - i as usize);
+            assert_eq!(rest.iter().len(), 20 - i as usize);
+            rest = rest.tail();
+        }
+        assert!(rest.is_empty());
+
+        // Versions share their trees, so elements which weren't changed are the same ones
+        let changed = list.set(0, 100).unwrap();
+        assert!(std::ptr::eq(changed.get(19).unwrap(), list.get(19).unwrap()));
+        assert!(std::ptr::eq(list.tail().get(5).unwrap(), list.get(6).unwrap()));
+        assert_eq!(list.tail().cons(0), list);
+    }
+
+    #[test]
+    fn conversions() {
+        let list: List<i32> = (1..=10).collect();
+        let random_access = RandomAccessList::from(&list);
+        assert_eq!(random_access.get(4), Some(&5));
+        assert_eq!(collect(&random_access), (1..=10).collect::<Vec<_>>());
+        assert_eq!(List::from(&random_access), list);
+        assert!(RandomAccessList::<i32>::from(&List::new()).is_empty());
+    }
+
+    #[test]
+    fn long_list() {
+        let list: RandomAccessList<u32> = (0..1_000_000).collect();
+        assert_eq!(list.get(999_999), Some(&999_999));
+        assert_eq!(list.set(500_000, 0).unwrap().get(500_000), Some(&0));
+        assert_eq!(list.iter().len(), 1_000_000);
+        assert_eq!(List::from(&list).len(), 1_000_000);
+    }
+}
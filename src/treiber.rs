@@ -0,0 +1,508 @@
+use std::iter;
+use std::mem::ManuallyDrop;
+use std::ptr;
+
+#[cfg(loom)]
+use loom::cell::UnsafeCell;
+#[cfg(loom)]
+use loom::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+#[cfg(not(loom))]
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use crate::second;
+
+/// How many popped nodes can be waiting to be freed before a pop tries to free them.
+/// Under loom every pop tries, so that the tests explore freeing alongside the other threads.
+const RECLAIM_THRESHOLD: usize = if cfg!(loom) { 1 } else { 64 };
+
+/// A lock-free version of second::List (a Treiber stack), which any number of threads can
+/// push onto and pop from at once through a shared reference.
+/// Pushing and popping swap the head pointer with a compare-and-swap, retrying if another
+/// thread got there first. The hard part is freeing popped nodes, since another thread may
+/// still be reading one it loaded as the head just before it was popped. Before reading a
+/// node, a pop publishes its address in a hazard pointer and checks it's still the head, and
+/// popped nodes are only freed once no hazard points to them.
+pub struct List<T> {
+    head: AtomicPtr<Node<T>>,
+    hazards: AtomicPtr<Hazard<T>>,
+    // Popped nodes which haven't been freed yet, linked through next_retired
+    retired: AtomicPtr<Node<T>>,
+    retired_len: AtomicUsize,
+}
+
+struct Node<T> {
+    // Moved out by whichever thread pops the node, so never dropped along with it
+    elem: UnsafeCell<ManuallyDrop<T>>,
+    // Set before the node is pushed and never changed afterwards
+    next: UnsafeCell<*mut Node<T>>,
+    // Only touched by the thread retiring or freeing the node
+    next_retired: *mut Node<T>,
+}
+
+/// A slot where a popping thread publishes the node it's about to read. Hazards are added
+/// as more threads pop at once, reused by later pops, and only freed along with the list.
+struct Hazard<T> {
+    node: AtomicPtr<Node<T>>,
+    active: AtomicBool,
+    // Set before the hazard is added and never changed afterwards
+    next: *mut Hazard<T>,
+}
+
+impl<T> Hazard<T> {
+    /// Stops protecting the node and lets another pop claim the hazard.
+    fn release(&self) {
+        self.node.store(ptr::null_mut(), Ordering::Release);
+        self.active.store(false, Ordering::Release);
+    }
+}
+
+/// std's UnsafeCell with loom's interface, so that under loom every access to a node is
+/// checked against the thread which frees it.
+#[cfg(not(loom))]
+struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    fn new(value: T) -> Self {
+        UnsafeCell(std::cell::UnsafeCell::new(value))
+    }
+
+    fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List {
+            head: AtomicPtr::new(ptr::null_mut()),
+            hazards: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            retired_len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether the stack was empty when checked. Other threads may have changed it since.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node {
+            elem: UnsafeCell::new(ManuallyDrop::new(elem)),
+            next: UnsafeCell::new(ptr::null_mut()),
+            next_retired: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: no other thread can see the node until the exchange succeeds.
+            unsafe { (*node).next.with_mut(|next| *next = head) };
+            match self.head.compare_exchange(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let hazard = self.acquire_hazard();
+        let head = loop {
+            let head = self.head.load(Ordering::Acquire);
+            if head.is_null() {
+                break head;
+            }
+            // Release so that whoever sees the hazard move on also sees our reads of the old node
+            hazard.node.store(head, Ordering::Release);
+            // Pairs with the fence in reclaim: either reclaim sees the hazard and keeps the
+            // node, or it freed the node after it was popped and we see the head has moved on.
+            fence(Ordering::SeqCst);
+            if self.head.load(Ordering::Acquire) != head {
+                continue;
+            }
+            // SAFETY: the hazard stops head being freed, and next never changes once pushed.
+            let next = unsafe { (*head).next.with(|next| *next) };
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                break head;
+            }
+        };
+        hazard.release();
+        if head.is_null() {
+            return None;
+        }
+        // SAFETY: winning the exchange means no other thread will take this node's element.
+        let elem = unsafe { (*head).elem.with_mut(|elem| ManuallyDrop::take(&mut *elem)) };
+        self.retire(head);
+        Some(elem)
+    }
+
+    /// Peeking needs exclusive access: through a shared reference another thread could pop
+    /// the element and drop it while it was being looked at.
+    pub fn peek(&mut self) -> Option<&T> {
+        // SAFETY: the mutable borrow of self means no other thread can pop the head.
+        unsafe {
+            self.head
+                .load(Ordering::Acquire)
+                .as_ref()
+                .map(|node| node.elem.with(|elem| &**elem))
+        }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as for peek.
+        unsafe {
+            self.head
+                .load(Ordering::Acquire)
+                .as_ref()
+                .map(|node| node.elem.with_mut(|elem| &mut **elem))
+        }
+    }
+
+    /// Takes every element off the stack in one go, returning them as an ordinary list with
+    /// the top of the stack at its front.
+    pub fn pop_all(&self) -> second::List<T> {
+        let mut node = self.head.swap(ptr::null_mut(), Ordering::Acquire);
+        iter::from_fn(|| {
+            if node.is_null() {
+                return None;
+            }
+            let popped = node;
+            // SAFETY: the swap took the whole stack, so no other thread will take these
+            // elements. Other threads may still be reading next, but that never changes.
+            let elem = unsafe {
+                node = (*popped).next.with(|next| *next);
+                (*popped).elem.with_mut(|elem| ManuallyDrop::take(&mut *elem))
+            };
+            self.retire(popped);
+            Some(elem)
+        })
+        .collect()
+    }
+
+    /// Claims an unused hazard, adding a new one if they're all in use.
+    fn acquire_hazard(&self) -> &Hazard<T> {
+        let mut hazard = self.hazards.load(Ordering::Acquire);
+        // SAFETY: hazards are only freed along with the list.
+        while let Some(existing) = unsafe { hazard.as_ref() } {
+            if existing
+                .active
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                return existing;
+            }
+            hazard = existing.next;
+        }
+        let hazard = Box::into_raw(Box::new(Hazard {
+            node: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.hazards.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: no other thread can see the hazard until the exchange succeeds, and
+            // afterwards it lives as long as the list.
+            unsafe { (*hazard).next = head };
+            match self.hazards.compare_exchange(head, hazard, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return unsafe { &*hazard },
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Queues a popped node to be freed once no hazard points to it, freeing any
+    /// unprotected nodes if enough have built up.
+    fn retire(&self, node: *mut Node<T>) {
+        // Counted before it's queued, so that the count can't go below zero when another
+        // thread frees the node straight away
+        let retired_len = self.retired_len.fetch_add(1, Ordering::Relaxed) + 1;
+        self.push_retired(node);
+        if retired_len >= RECLAIM_THRESHOLD {
+            self.reclaim();
+        }
+    }
+
+    fn push_retired(&self, node: *mut Node<T>) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the node has been popped, so only this thread can reach it.
+            unsafe { (*node).next_retired = head };
+            match self.retired.compare_exchange(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Frees the retired nodes which no hazard points to, and queues the rest again.
+    fn reclaim(&self) {
+        let mut node = self.retired.swap(ptr::null_mut(), Ordering::Acquire);
+        // Pairs with the fence in pop.
+        fence(Ordering::SeqCst);
+        let mut protected = Vec::new();
+        let mut hazard = self.hazards.load(Ordering::Acquire);
+        // SAFETY: as in acquire_hazard.
+        while let Some(existing) = unsafe { hazard.as_ref() } {
+            protected.push(existing.node.load(Ordering::Acquire));
+            hazard = existing.next;
+        }
+        while !node.is_null() {
+            // SAFETY: the swap took these nodes off the retired list, so only this thread has them.
+            let next = unsafe { (*node).next_retired };
+            if protected.contains(&node) {
+                self.push_retired(node);
+            } else {
+                // SAFETY: the node has been popped and no hazard protects it, so no other
+                // thread can be reading it or start to.
+                unsafe { free(node) };
+                self.retired_len.fetch_sub(1, Ordering::Relaxed);
+            }
+            node = next;
+        }
+    }
+}
+
+/// Frees a node without dropping its element.
+///
+/// # Safety
+/// The node must have come from Box::into_raw and no other thread can be using it.
+unsafe fn free<T>(node: *mut Node<T>) {
+    // SAFETY: guaranteed by the caller. Marking the fields as written lets loom check that
+    // every earlier read happened before the node is freed.
+    unsafe {
+        (*node).elem.with_mut(|_| ());
+        (*node).next.with_mut(|_| ());
+        drop(Box::from_raw(node));
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> crate::Stack<T> for List<T> {
+    fn push(&mut self, elem: T) {
+        List::push(self, elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        List::pop(self)
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // With exclusive access no other thread is popping, so nothing is protected
+        let mut node = self.head.load(Ordering::Relaxed);
+        while !node.is_null() {
+            let popped = node;
+            // SAFETY: the nodes on the stack belong to it and still hold their elements.
+            unsafe {
+                node = (*popped).next.with(|next| *next);
+                (*popped).elem.with_mut(|elem| ManuallyDrop::drop(&mut *elem));
+                free(popped);
+            }
+        }
+        let mut node = self.retired.load(Ordering::Relaxed);
+        while !node.is_null() {
+            let retired = node;
+            // SAFETY: retired nodes belong to the list and their elements have been taken.
+            unsafe {
+                node = (*retired).next_retired;
+                free(retired);
+            }
+        }
+        let mut hazard = self.hazards.load(Ordering::Relaxed);
+        while !hazard.is_null() {
+            // SAFETY: hazards came from Box::into_raw and belong to the list.
+            let boxed = unsafe { Box::from_raw(hazard) };
+            hazard = boxed.next;
+        }
+    }
+}
+
+// SAFETY: elements are only ever moved between threads, never shared, so the list is
+// Send and Sync as long as they're Send, like a Mutex.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Send> Sync for List<T> {}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use super::List;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let list = List::new();
+        assert_eq!(list.pop(), None);
+        assert!(list.is_empty());
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(2));
+
+        list.push(4);
+        list.push(5);
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.peek(), Some(&2));
+        if let Some(value) = list.peek_mut() {
+            *value = 42;
+        }
+        assert_eq!(list.pop(), Some(42));
+        assert_eq!(list.peek(), Some(&1));
+    }
+
+    #[test]
+    fn pop_all() {
+        let list = List::new();
+        assert!(list.pop_all().is_empty());
+        for elem in 1..=3 {
+            list.push(elem);
+        }
+        let popped = list.pop_all();
+        assert!(list.is_empty());
+        assert_eq!(popped.into_iter().collect::<Vec<_>>(), vec![3, 2, 1]);
+        list.push(4);
+        assert_eq!(list.pop(), Some(4));
+    }
+
+    #[test]
+    fn send_sync() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<List<i32>>();
+        is_send_sync::<List<std::cell::Cell<i32>>>();
+    }
+
+    /// Every thread pushes its own elements while popping whatever it finds, and between
+    /// them they must pop each element exactly once. Elements left on the stack when it's
+    /// dropped have to be freed with it, which the token they each clone keeps count of.
+    #[test]
+    fn stress() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 10_000;
+        let token = Arc::new(());
+        let list = Arc::new(List::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let list = list.clone();
+                let token = token.clone();
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..PER_THREAD {
+                        list.push((thread * PER_THREAD + i, token.clone()));
+                        if i % 3 != 0 {
+                            popped.extend(list.pop().map(|(elem, _)| elem));
+                        }
+                        if i % 1000 == 0 {
+                            popped.extend(list.pop_all().into_iter().map(|(elem, _)| elem));
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+        let mut popped: Vec<_> = handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect();
+        let list = Arc::try_unwrap(list).ok().unwrap();
+        popped.extend(list.pop_all().into_iter().map(|(elem, _)| elem));
+        popped.sort_unstable();
+        assert_eq!(popped, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+
+        for elem in 0..100 {
+            list.push((elem, token.clone()));
+        }
+        drop(list);
+        assert_eq!(Arc::strong_count(&token), 1);
+    }
+}
+
+/// Run with `LOOM_MAX_PREEMPTIONS=3 RUSTFLAGS="--cfg loom" cargo test --release treiber`, which
+/// checks every interleaving of the threads (up to three preemptions each), including the ones
+/// that free nodes.
+#[cfg(all(test, loom))]
+mod loom_test {
+    use super::List;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn concurrent_pops() {
+        loom::model(|| {
+            let list = Arc::new(List::new());
+            list.push(1);
+            list.push(2);
+            let other = {
+                let list = list.clone();
+                thread::spawn(move || list.pop())
+            };
+            let mut popped = vec![list.pop().unwrap(), other.join().unwrap().unwrap()];
+            popped.sort_unstable();
+            assert_eq!(popped, vec![1, 2]);
+            assert_eq!(list.pop(), None);
+        });
+    }
+
+    #[test]
+    fn push_during_pops() {
+        loom::model(|| {
+            let list = Arc::new(List::new());
+            list.push(1);
+            let pusher = {
+                let list = list.clone();
+                thread::spawn(move || list.push(2))
+            };
+            let popper = {
+                let list = list.clone();
+                thread::spawn(move || list.pop())
+            };
+            let mut popped: Vec<_> = list.pop().into_iter().collect();
+            pusher.join().unwrap();
+            popped.extend(popper.join().unwrap());
+            popped.extend(list.pop_all());
+            popped.sort_unstable();
+            assert_eq!(popped, vec![1, 2]);
+        });
+    }
+
+    /// The elements are loom Arcs, which loom reports if any are leaked.
+    #[test]
+    fn pop_all_during_pop() {
+        loom::model(|| {
+            let list = Arc::new(List::new());
+            let token = Arc::new(());
+            list.push(token.clone());
+            list.push(token.clone());
+            let other = {
+                let list = list.clone();
+                thread::spawn(move || list.pop().is_some())
+            };
+            let taken = list.pop_all().len();
+            let popped = other.join().unwrap();
+            assert_eq!(taken + popped as usize, 2);
+            assert!(list.is_empty());
+        });
+    }
+}